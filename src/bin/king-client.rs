@@ -0,0 +1,122 @@
+/*
+    Terminal client for king-server.
+        cargo run --bin king-client -- Ilia 127.0.0.1:7878
+*/
+
+use std::env;
+use std::io;
+
+use king::game::card::{Card, Suit};
+use king::net::client::Client;
+use king::net::protocol::{ClientMessage, Request, ServerMessage};
+
+fn show(cards: &[Card]) -> String {
+    cards.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+/* Name of a seat the server told about, a bad seat number shows as "?" */
+fn seat_name(names: &[String], seat: usize) -> &str {
+    names.get(seat).map_or("?", String::as_str)
+}
+
+/* Every seat's name with its score */
+fn show_scores(names: &[String], scores: &[i32]) -> String {
+    let scores: Vec<String> = scores.iter().enumerate().map(|(seat, s)| format!("{} {}", seat_name(names, seat), s)).collect();
+    scores.join(", ")
+}
+
+fn read_line() -> Option<String> {
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+/* Keep asking until the line parses, None when stdin is closed */
+fn prompt(request: Request, legal: &[Card]) -> Option<ClientMessage> {
+    loop {
+        match request {
+            Request::Play => println!("Your turn, playable: {}", show(legal)),
+            Request::Hide => println!("Hide two cards (e.g. `7S 8D`), allowed: {}", show(legal)),
+            Request::Trump => println!("Choose trumps (H, D, S or C)"),
+        }
+        let line = read_line()?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let message = match (request, words.as_slice()) {
+            (Request::Play, [card]) => card.parse().ok().map(|card| ClientMessage::Play { card }),
+            (Request::Hide, [first, second]) => match (first.parse(), second.parse()) {
+                (Ok(a), Ok(b)) => Some(ClientMessage::Hide { cards: [a, b] }),
+                _ => None,
+            },
+            (Request::Trump, [suit]) => suit.parse::<Suit>().ok().map(|suit| ClientMessage::Trump { suit }),
+            _ => None,
+        };
+        match message {
+            Some(message) => return Some(message),
+            None => println!("Could not read that, try again"),
+        }
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let name = args.next().unwrap_or_else(|| String::from("Player"));
+    let address = args.next().unwrap_or_else(|| String::from("127.0.0.1:7878"));
+
+    let mut client = match Client::connect(&address, &name) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Can not connect to {}: {}", address, err);
+            return;
+        }
+    };
+
+    let mut names: Vec<String> = vec![String::new(); 3];
+    loop {
+        let message = match client.receive() {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("Connection lost: {}", err);
+                break;
+            }
+        };
+        match message {
+            ServerMessage::Welcome { seat } => println!("You sit at seat {}", seat),
+            ServerMessage::Join { seat, name } => {
+                println!("{} joined", name);
+                if let Some(slot) = names.get_mut(seat) {
+                    *slot = name;
+                }
+            },
+            ServerMessage::Deal { contract, declarer, hand } => {
+                println!("<-------------------{}------------------->", contract.name());
+                println!("{} declares", seat_name(&names, declarer));
+                println!("Your hand: {}", show(&hand));
+            },
+            ServerMessage::YourTurn { request, legal } => {
+                let Some(answer) = prompt(request, &legal) else { break };
+                if client.send(&answer).is_err() {
+                    break;
+                }
+            },
+            ServerMessage::CardsHidden { seat, cards } => match cards {
+                Some(cards) => println!("You hid {}", show(&cards)),
+                None => println!("{} hid two cards", seat_name(&names, seat)),
+            },
+            ServerMessage::TrumpChosen { seat, suit } => println!("{} chose {} as trumps", seat_name(&names, seat), suit),
+            ServerMessage::Play { seat, card } => println!("{} plays {}", seat_name(&names, seat), card),
+            ServerMessage::TrickWon { seat, cards } => println!("{} takes {}", seat_name(&names, seat), show(&cards)),
+            ServerMessage::ScoreUpdate { seat, delta, total } => {
+                println!("{} gets {} ({} in total)", seat_name(&names, seat), delta, total)
+            },
+            ServerMessage::DealOver { scores } => println!("Round has ended, scores: {}", show_scores(&names, &scores)),
+            ServerMessage::GameOver { scores } => {
+                println!("Game over, scores: {}", show_scores(&names, &scores));
+                break;
+            },
+            ServerMessage::Error { message } => println!("Server: {}", message),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+/*
+    Duplicate sessions. Writes a deal file, or plays one at three tables with
+    the entrants rotated and prints the cross-table report.
+        cargo run --bin king-duplicate -- generate 6 42 > deals.txt
+        cargo run --bin king-duplicate -- play deals.txt heuristic ismcts random
+*/
+
+use std::env;
+
+use king::bots;
+use king::duplicate::{self, Entrant};
+
+fn usage() {
+    eprintln!("Usage: king-duplicate generate <count> [seed]");
+    eprintln!("       king-duplicate play <deal file> [human|{}] x3", bots::BOT_NAMES.join("|"));
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("generate") => {
+            let count = args.get(1).and_then(|c| c.parse().ok());
+            let seed = args.get(2).map_or(Some(rand::random()), |s| s.parse().ok());
+            match (count, seed) {
+                (Some(count), Some(seed)) if count <= king::game::schedule::DEALS => {
+                    print!("{}", duplicate::format_deals(&duplicate::generate_deals(count, seed)));
+                },
+                _ => usage(),
+            }
+        },
+        Some("play") if args.len() == 5 => {
+            let deals = match duplicate::load_deals(&args[1]) {
+                Ok(deals) => deals,
+                Err(err) => {
+                    eprintln!("Could not read {}: {}", args[1], err);
+                    return;
+                }
+            };
+            /* Entrants are named after their kind and first seat */
+            let entrants: Vec<Entrant> = args[2..]
+                .iter()
+                .enumerate()
+                .map(|(seat, kind)| Entrant { name: format!("{}-{}", kind, seat + 1), kind: kind.clone() })
+                .collect();
+            let names: Vec<String> = entrants.iter().map(|e| e.name.clone()).collect();
+
+            match duplicate::run(&deals, &entrants, 3) {
+                Ok(results) => print!("{}", duplicate::compare(&deals, &names, &results)),
+                Err(err) => eprintln!("{}", err),
+            }
+        },
+        _ => usage(),
+    }
+}
@@ -0,0 +1,43 @@
+/*
+    Hosts one match for three network players. The optional second address
+    opens the WebSocket gateway for browser clients next to the TCP port.
+        cargo run --bin king-server -- 127.0.0.1:7878 127.0.0.1:7879
+*/
+
+use std::env;
+use std::net::TcpListener;
+
+use king::net::server;
+
+fn main() {
+    let address = env::args().nth(1).unwrap_or_else(|| String::from("127.0.0.1:7878"));
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Can not listen on {}: {}", address, err);
+            return;
+        }
+    };
+    println!("Waiting for three players on {}", address);
+
+    let websocket = match env::args().nth(2).map(|address| (TcpListener::bind(&address), address)) {
+        None => None,
+        Some((Ok(listener), address)) => {
+            println!("WebSocket gateway on ws://{}", address);
+            Some(listener)
+        },
+        Some((Err(err), address)) => {
+            eprintln!("Can not listen on {}: {}", address, err);
+            return;
+        }
+    };
+
+    match server::serve(&listener, websocket.as_ref()) {
+        Ok(engine) => {
+            for player in &engine.players {
+                println!("{}: {}", player.username, player.score);
+            }
+        },
+        Err(err) => eprintln!("Server stopped: {}", err),
+    }
+}
@@ -0,0 +1,113 @@
+/*
+    Computer opponents. Every bot is a `PlayerController`, so it can take any
+    seat of the console game next to humans.
+*/
+
+pub mod random;
+pub mod heuristic;
+pub mod ismcts;
+
+use crate::game::board;
+use crate::game::card::{Card, Suit};
+use crate::game::controller::PlayerController;
+
+pub use heuristic::HeuristicBot;
+pub use ismcts::IsmctsBot;
+pub use random::RandomBot;
+
+/* Names accepted by `controller_by_name`, besides "human" which the front end handles */
+pub const BOT_NAMES: [&str; 3] = ["random", "heuristic", "ismcts"];
+
+/* Build a bot from its name as typed on the command line */
+pub fn controller_by_name(name: &str) -> Option<Box<dyn PlayerController>> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "random" => Some(Box::new(RandomBot::new())),
+        "heuristic" => Some(Box::new(HeuristicBot::new())),
+        "ismcts" => Some(Box::new(IsmctsBot::new())),
+        _ => None,
+    }
+}
+
+/* Card currently taking the unfinished trick, None on an empty board */
+pub fn winning_card(board: &[Card], trump: Option<Suit>) -> Option<Card> {
+    board::trick_winner(board, trump).map(|position| board[position])
+}
+
+/* Whether laying down `card` now would put it on top of the trick */
+pub fn beats(board: &[Card], card: Card, trump: Option<Suit>) -> bool {
+    let mut trick = board.to_vec();
+    trick.push(card);
+    winning_card(&trick, trump) == Some(card)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::engine::{Action, Decision, Event, GameEngine};
+    use crate::game::observation;
+
+    /* Let the bots play deals on their own, failing on the first rejected answer */
+    fn play_deals(mut bots: Vec<Box<dyn PlayerController>>, deals: usize) {
+        let mut engine = GameEngine::new();
+        for (seat, name) in ["A", "B", "C"].iter().enumerate() {
+            engine.add_player(name.to_string(), seat == 0).unwrap();
+        }
+
+        let mut finished = 0;
+        while finished < deals {
+            let action = match engine.pending_decision().unwrap() {
+                Decision::StartDeal => Action::StartDeal,
+                Decision::HideCards { seat } => {
+                    Action::HideCards { seat, cards: bots[seat].choose_cards_to_hide(&engine.observation(seat)).unwrap() }
+                },
+                Decision::ChooseTrump { seat } => {
+                    Action::ChooseTrump { seat, trump: bots[seat].choose_trump(&engine.observation(seat)).unwrap() }
+                },
+                Decision::PlayCard { seat } => {
+                    Action::PlayCard { seat, card: bots[seat].choose_card(&engine.observation(seat)).unwrap() }
+                },
+            };
+            let events = engine.apply(action.clone()).unwrap_or_else(|e| panic!("{:?} rejected: {}", action, e));
+            for event in &events {
+                for (seat, bot) in bots.iter_mut().enumerate() {
+                    bot.notify(&engine.observation(seat), &observation::redact(event, seat));
+                }
+            }
+            finished += events.iter().filter(|e| **e == Event::DealEnded).count();
+        }
+    }
+
+    #[test]
+    fn winning_card_prefers_trump_then_lead_suit() {
+        let cards = |list: &[&str]| -> Vec<Card> { list.iter().map(|c| c.parse().unwrap()).collect() };
+        assert_eq!(winning_card(&cards(&["9S", "AS", "AD"]), None), Some("AS".parse().unwrap()));
+        assert_eq!(winning_card(&cards(&["9S", "AS", "7D"]), Some(Suit::Diamonds)), Some("7D".parse().unwrap()));
+        assert_eq!(winning_card(&[], None), None);
+    }
+
+    #[test]
+    fn random_bots_only_make_legal_choices() {
+        let bots: Vec<Box<dyn PlayerController>> = (0..3)
+            .map(|seed| Box::new(RandomBot::with_seed(seed)) as Box<dyn PlayerController>)
+            .collect();
+        play_deals(bots, 27);
+    }
+
+    #[test]
+    fn heuristic_bots_only_make_legal_choices() {
+        let bots: Vec<Box<dyn PlayerController>> = (0..3)
+            .map(|_| Box::new(HeuristicBot::new()) as Box<dyn PlayerController>)
+            .collect();
+        play_deals(bots, 27);
+    }
+
+    #[test]
+    fn ismcts_bot_only_makes_legal_choices() {
+        let bots: Vec<Box<dyn PlayerController>> = vec![
+            Box::new(IsmctsBot::new().with_iterations(30).with_seed(1)),
+            Box::new(RandomBot::with_seed(2)),
+            Box::new(HeuristicBot::new()),
+        ];
+        play_deals(bots, 27);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::bots::beats;
+use crate::game::card::{Card, Suit};
+use crate::game::contract::Contract;
+use crate::game::controller::PlayerController;
+use crate::game::engine::GameError;
+use crate::game::observation::Observation;
+
+/*
+    Rule-of-thumb player that knows what every contract is about:
+    in '+' it tries to take tricks, in the other games it ducks under the
+    current winner and dumps the penalty cards (King Heart, queens, jacks,
+    hearts) whenever somebody else is taking the trick. In '2' only the
+    last two tricks matter, so it gets rid of its high cards before them.
+*/
+#[derive(Default)]
+pub struct HeuristicBot;
+
+impl HeuristicBot {
+    pub fn new() -> Self {
+        HeuristicBot
+    }
+
+    fn lowest(cards: &[Card]) -> Option<Card> {
+        cards.iter().min_by_key(|c| c.rank).copied()
+    }
+
+    fn highest(cards: &[Card]) -> Option<Card> {
+        cards.iter().max_by_key(|c| c.rank).copied()
+    }
+
+    /* Prefer cards passing the filter, fall back to all of them */
+    fn preferring(cards: &[Card], keep: impl Fn(&Card) -> bool) -> Vec<Card> {
+        let preferred: Vec<Card> = cards.iter().filter(|c| keep(c)).copied().collect();
+        if preferred.is_empty() { cards.to_vec() } else { preferred }
+    }
+
+    /* '+' game: win the trick as cheaply as possible */
+    fn take_trick(board: &[Card], legal: &[Card], trump: Option<Suit>) -> Card {
+        if board.is_empty() {
+            return Self::highest(legal).unwrap();
+        }
+        let winners: Vec<Card> = legal.iter().filter(|c| beats(board, **c, trump)).copied().collect();
+        if winners.is_empty() {
+            return Self::lowest(legal).unwrap();
+        }
+        /* The last player only has to beat what is on the board */
+        if board.len() == 2 {
+            Self::lowest(&winners).unwrap()
+        } else {
+            Self::highest(&winners).unwrap()
+        }
+    }
+
+    /* Negative games: stay under the current winner and dump penalty cards on others */
+    fn avoid_trick(contract: Contract, board: &[Card], legal: &[Card], trump: Option<Suit>) -> Card {
+        let harmless = |c: &Card| !contract.is_penalty_card(c);
+
+        if board.is_empty() {
+            return Self::lowest(&Self::preferring(legal, harmless)).unwrap();
+        }
+
+        let losing: Vec<Card> = legal.iter().filter(|c| !beats(board, **c, trump)).copied().collect();
+        if !losing.is_empty() {
+            let penalties: Vec<Card> = losing.iter().filter(|c| !harmless(c)).copied().collect();
+            return Self::highest(&penalties)
+                .or_else(|| Self::highest(&losing))
+                .unwrap();
+        }
+
+        /* Every card wins: the last player takes the trick anyway, so spend the highest one */
+        let candidates = Self::preferring(legal, harmless);
+        if board.len() == 2 {
+            Self::highest(&candidates).unwrap()
+        } else {
+            Self::lowest(&candidates).unwrap()
+        }
+    }
+
+    /* Longest suit, the stronger one on ties */
+    fn best_trump(hand: &[Card]) -> Suit {
+        *Suit::ALL
+            .iter()
+            .max_by_key(|suit| {
+                let cards: Vec<&Card> = hand.iter().filter(|c| c.suit == **suit).collect();
+                let strength: usize = cards.iter().map(|c| c.rank as usize).sum();
+                (cards.len(), strength)
+            })
+            .unwrap()
+    }
+}
+
+impl PlayerController for HeuristicBot {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        let legal = &seen.legal_moves;
+        let board: Vec<Card> = seen.trick.iter().map(|(_, card)| *card).collect();
+        let trump = seen.trump;
+        let contract = seen.contract.expect("Cards are only played during a deal");
+
+        let card = match contract {
+            Contract::Plus => Self::take_trick(&board, legal, trump),
+            /* Tricks before the last two cost nothing, shed high cards there */
+            Contract::LastTwo if seen.hand.len() > 2 => Self::highest(legal).unwrap(),
+            _ => Self::avoid_trick(contract, &board, legal, trump),
+        };
+        Ok(card)
+    }
+
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError> {
+        Ok(Self::best_trump(&seen.hand))
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        let hand = &seen.hand;
+        let mut allowed: Vec<Card> = seen.discardable();
+
+        if seen.contract == Some(Contract::Plus) {
+            /* Keep the future trump suit, hide the weakest of the rest */
+            let trump = Self::best_trump(hand);
+            allowed.sort_by_key(|c| (c.suit == trump, c.rank));
+        } else {
+            /* High cards take tricks, get rid of them */
+            allowed.sort_by_key(|c| std::cmp::Reverse(c.rank));
+        }
+
+        /* Best legal pair in that order, penalty cards only where the rules force them */
+        let order = |card: &Card| allowed.iter().position(|c| c == card);
+        let pair = seen.discard_pairs
+            .iter()
+            .copied()
+            .min_by_key(|pair| {
+                let mut places = pair.map(|c| order(&c));
+                places.sort();
+                places
+            })
+            .expect("A declarer always has two cards to hide");
+        Ok(pair)
+    }
+}
@@ -0,0 +1,316 @@
+/*
+    Information-Set Monte Carlo Tree Search (single observer).
+
+    Every iteration deals the unseen cards to the opponents at random, keeping
+    the voids they revealed by not following suit, and then walks one shared
+    tree of card choices with UCB, expands one node and plays the deal out at
+    random on an engine built from the seat's observation and those guessed
+    hands. The reward of a seat is what the engine's scoring gave it during
+    the rest of the deal.
+
+    Trump choice and hidden cards are left to the heuristic bot.
+*/
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::bots::HeuristicBot;
+use crate::game::card::{self, Card, Suit};
+use crate::game::controller::PlayerController;
+use crate::game::engine::{Action, Decision, Event, GameEngine, GameError};
+use crate::game::observation::Observation;
+
+const DEFAULT_ITERATIONS: usize = 1000;
+const DEFAULT_EXPLORATION: f64 = 0.7;
+
+/* One card choice in the search tree */
+struct Node {
+    card: Option<Card>,   // None for the root
+    seat: usize,          // who laid the card down
+    children: Vec<usize>, // indices into the arena
+    visits: u32,
+    availability: u32,
+    reward: f64,
+}
+
+impl Node {
+    fn new(card: Option<Card>, seat: usize) -> Self {
+        Node { card, seat, children: vec![], visits: 0, availability: 0, reward: 0.0 }
+    }
+}
+
+pub struct IsmctsBot {
+    iterations: usize,
+    time_limit: Option<Duration>,
+    exploration: f64,
+    rng: StdRng,
+    fallback: HeuristicBot,
+    /* What this seat has seen during the current deal */
+    voids: [[bool; 4]; 3],
+    trick: Vec<(usize, Card)>,
+}
+
+impl Default for IsmctsBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IsmctsBot {
+    pub fn new() -> Self {
+        IsmctsBot {
+            iterations: DEFAULT_ITERATIONS,
+            time_limit: None,
+            exploration: DEFAULT_EXPLORATION,
+            rng: StdRng::from_entropy(),
+            fallback: HeuristicBot::new(),
+            voids: [[false; 4]; 3],
+            trick: vec![],
+        }
+    }
+
+    /* Number of play-outs per decision */
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /* Stop searching after this long even if iterations are left */
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /* UCB exploration constant */
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    fn suit_idx(suit: Suit) -> usize {
+        Suit::ALL.iter().position(|s| *s == suit).unwrap()
+    }
+
+    /* Hand out the cards this seat can not see, respecting known voids */
+    fn determinize(&mut self, seen: &Observation) -> GameEngine {
+        let seat = seen.seat;
+        let known: HashSet<Card> = seen.hand
+            .iter()
+            .chain(&seen.played)
+            .chain(seen.hidden.iter().flatten())
+            .copied()
+            .collect();
+        let mut unseen: Vec<Card> = card::fill_cards()
+            .into_iter()
+            .filter(|c| !known.contains(c))
+            .collect();
+
+        let opponents: Vec<usize> = (0..3).filter(|s| *s != seat).collect();
+        /* Only the number of cards each opponent holds, which the whole table can count */
+        let sizes: Vec<usize> = opponents.iter().map(|s| seen.hand_sizes[*s]).collect();
+
+        let mut hands: Vec<Vec<Card>> = vec![];
+        for attempt in 0..50 {
+            /* Give up on voids when they can not be satisfied */
+            let respect_voids = attempt < 49;
+            unseen.shuffle(&mut self.rng);
+            if let Some(dealt) = self.try_deal(&unseen, &opponents, &sizes, respect_voids) {
+                hands = dealt;
+                break;
+            }
+        }
+
+        let mut dealt = [vec![], vec![], vec![]];
+        dealt[seat] = seen.hand.clone();
+        for (i, s) in opponents.iter().enumerate() {
+            dealt[*s] = hands[i].clone();
+        }
+        GameEngine::from_observation(seen, dealt)
+    }
+
+    fn try_deal(
+        &mut self,
+        unseen: &[Card],
+        opponents: &[usize],
+        sizes: &[usize],
+        respect_voids: bool,
+    ) -> Option<Vec<Vec<Card>>> {
+        let mut hands: Vec<Vec<Card>> = vec![vec![]; opponents.len()];
+        /* Whatever is left over stays in the declarer's hidden cards */
+        let mut talon_room = unseen.len().saturating_sub(sizes.iter().sum());
+
+        for c in unseen {
+            let suit = Self::suit_idx(c.suit);
+            let mut room: Vec<(usize, usize)> = (0..opponents.len())
+                .filter(|i| hands[*i].len() < sizes[*i])
+                .filter(|i| !respect_voids || !self.voids[opponents[*i]][suit])
+                .map(|i| (i, sizes[i] - hands[i].len()))
+                .collect();
+            if talon_room > 0 {
+                room.push((usize::MAX, talon_room));
+            }
+
+            let total: usize = room.iter().map(|(_, r)| r).sum();
+            if total == 0 {
+                return None;
+            }
+            let mut pick = self.rng.gen_range(0..total);
+            for (i, r) in room {
+                if pick < r {
+                    if i == usize::MAX {
+                        talon_room -= 1;
+                    } else {
+                        hands[i].push(*c);
+                    }
+                    break;
+                }
+                pick -= r;
+            }
+        }
+        Some(hands)
+    }
+
+    /* Score of every seat from now until the end of the deal, random play */
+    fn rollout(&mut self, world: &mut GameEngine, gained: &mut [i32; 3]) {
+        while let Some(Decision::PlayCard { seat }) = world.pending_decision() {
+            let card = *world.legal_moves(seat).choose(&mut self.rng).unwrap();
+            Self::play(world, seat, card, gained);
+        }
+    }
+
+    fn play(world: &mut GameEngine, seat: usize, card: Card, gained: &mut [i32; 3]) {
+        let events = world
+            .apply(Action::PlayCard { seat, card })
+            .expect("Search played an illegal card");
+        for event in events {
+            if let Event::ScoreChanged { seat, delta, .. } = event {
+                gained[seat] += delta;
+            }
+        }
+    }
+
+    fn ucb(&self, node: &Node) -> f64 {
+        let visits = node.visits.max(1) as f64;
+        let availability = node.availability.max(1) as f64;
+        node.reward / visits + self.exploration * (availability.ln() / visits).sqrt()
+    }
+
+    fn search(&mut self, seen: &Observation) -> Card {
+        let started = Instant::now();
+        let mut tree: Vec<Node> = vec![Node::new(None, seen.seat)];
+
+        for _ in 0..self.iterations {
+            if self.time_limit.is_some_and(|limit| started.elapsed() >= limit) {
+                break;
+            }
+
+            let mut world = self.determinize(seen);
+            let mut gained = [0; 3];
+            let mut path: Vec<usize> = vec![0];
+            let mut node = 0;
+
+            /* Selection and expansion */
+            while let Some(Decision::PlayCard { seat: mover }) = world.pending_decision() {
+                let legal = world.legal_moves(mover);
+                let available: Vec<usize> = tree[node].children
+                    .iter()
+                    .copied()
+                    .filter(|c| legal.contains(&tree[*c].card.unwrap()))
+                    .collect();
+                let untried: Vec<Card> = legal
+                    .iter()
+                    .copied()
+                    .filter(|c| !available.iter().any(|a| tree[*a].card == Some(*c)))
+                    .collect();
+
+                for a in &available {
+                    tree[*a].availability += 1;
+                }
+
+                if let Some(card) = untried.choose(&mut self.rng).copied() {
+                    tree.push(Node::new(Some(card), mover));
+                    let child = tree.len() - 1;
+                    tree[child].availability += 1;
+                    tree[node].children.push(child);
+                    path.push(child);
+                    Self::play(&mut world, mover, card, &mut gained);
+                    break;
+                }
+
+                let best = *available
+                    .iter()
+                    .max_by(|a, b| self.ucb(&tree[**a]).total_cmp(&self.ucb(&tree[**b])))
+                    .unwrap();
+                path.push(best);
+                node = best;
+                Self::play(&mut world, mover, tree[best].card.unwrap(), &mut gained);
+            }
+
+            self.rollout(&mut world, &mut gained);
+
+            /* Backpropagation, each node is judged by the seat that chose it */
+            for n in path {
+                let owner = tree[n].seat;
+                tree[n].visits += 1;
+                tree[n].reward += gained[owner] as f64 / 40.0;
+            }
+        }
+
+        let best = tree[0].children
+            .iter()
+            .max_by_key(|c| tree[**c].visits)
+            .and_then(|c| tree[*c].card);
+        best.unwrap_or(seen.legal_moves[0])
+    }
+}
+
+impl PlayerController for IsmctsBot {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        if seen.legal_moves.len() == 1 {
+            return Ok(seen.legal_moves[0]);
+        }
+        Ok(self.search(seen))
+    }
+
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError> {
+        self.fallback.choose_trump(seen)
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        self.fallback.choose_cards_to_hide(seen)
+    }
+
+    fn notify(&mut self, seen: &Observation, event: &Event) {
+        match event {
+            Event::DealStarted { .. } => {
+                self.voids = [[false; 4]; 3];
+                self.trick.clear();
+            },
+            Event::CardPlayed { seat, card } => {
+                if let Some((_, lead)) = self.trick.first() {
+                    if card.suit != lead.suit {
+                        self.voids[*seat][Self::suit_idx(lead.suit)] = true;
+                        /* Not following suit without trumping means no trumps either */
+                        if let Some(trump) = seen.trump {
+                            if card.suit != trump {
+                                self.voids[*seat][Self::suit_idx(trump)] = true;
+                            }
+                        }
+                    }
+                }
+                self.trick.push((*seat, *card));
+            },
+            Event::TrickWon { .. } => self.trick.clear(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::game::card::{Card, Suit};
+use crate::game::controller::PlayerController;
+use crate::game::engine::GameError;
+use crate::game::observation::Observation;
+
+/* Plays a uniformly random legal card, names a random trump and hides random allowed cards */
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl Default for RandomBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandomBot {
+    pub fn new() -> Self {
+        RandomBot { rng: StdRng::from_entropy() }
+    }
+
+    /* Same choices every run, handy for tests */
+    pub fn with_seed(seed: u64) -> Self {
+        RandomBot { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl PlayerController for RandomBot {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        Ok(*seen.legal_moves
+            .choose(&mut self.rng)
+            .expect("Bot asked to play without legal moves"))
+    }
+
+    fn choose_trump(&mut self, _seen: &Observation) -> Result<Suit, GameError> {
+        Ok(*Suit::ALL.choose(&mut self.rng).unwrap())
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        Ok(*seen.discard_pairs.choose(&mut self.rng).expect("A declarer always has two cards to hide"))
+    }
+}
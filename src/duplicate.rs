@@ -0,0 +1,310 @@
+/*
+    Duplicate King. A deal file fixes the cards of every deal of a session,
+    the session is played at several tables with the entrants moved one seat
+    further at every table, and the report compares what each of them made
+    of the same cards.
+
+    Deal file: one deal per line, the declarer's twelve cards first and then
+    the hands of the next two seats, separated by '|'. Blank lines and lines
+    starting with '#' are ignored.
+
+        # name: declarer | left | right
+        d1: AS KS ... | 7H 8H ... | 9D 10D ...
+
+    Deal n of the file is played as deal n of the schedule, so its contract
+    and declarer seat are the same at every table.
+*/
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::bots;
+use crate::game::card::{self, Card};
+use crate::game::contract::Contract;
+use crate::game::controller::{self, PlayerController, StdinController};
+use crate::game::deal::DealId;
+use crate::game::engine::{Decision, Event, GameEngine, GameError};
+use crate::game::schedule;
+
+/* Cards of one deal, the declarer's hand first */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PresetDeal {
+    pub name: String,
+    pub hands: [Vec<Card>; 3],
+}
+
+/* Entrant of a session, `kind` is "human" or one of `bots::BOT_NAMES` */
+#[derive(Clone, Debug)]
+pub struct Entrant {
+    pub name: String,
+    pub kind: String,
+}
+
+/* Outcome of the session at one table */
+#[derive(Clone, Debug)]
+pub struct TableResult {
+    pub table: usize,
+    pub seating: [usize; 3],          // entrant sitting at each seat
+    pub contracts: Vec<Contract>,     // per deal
+    pub scores: Vec<[i32; 3]>,        // per deal, by seat
+}
+
+impl TableResult {
+    /* Points the entrant made on the deal */
+    pub fn entrant_score(&self, deal: usize, entrant: usize) -> i32 {
+        let seat = self.seating.iter().position(|e| *e == entrant).unwrap();
+        self.scores[deal][seat]
+    }
+}
+
+pub fn parse_deals(text: &str) -> Result<Vec<PresetDeal>, String> {
+    let mut deals = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let deal = parse_deal(line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        deals.push(deal);
+    }
+    if deals.len() > schedule::DEALS {
+        return Err(format!("{} deals do not fit in one match of {}", deals.len(), schedule::DEALS));
+    }
+    Ok(deals)
+}
+
+fn parse_deal(line: &str) -> Result<PresetDeal, String> {
+    let (name, cards) = line.split_once(':').ok_or("expected 'name: hands'")?;
+    let hands: Vec<Vec<Card>> = cards
+        .split('|')
+        .map(|hand| hand.split_whitespace().map(|c| c.parse().map_err(|e| format!("{}", e))).collect())
+        .collect::<Result<_, String>>()?;
+
+    let hands: [Vec<Card>; 3] = hands.try_into().map_err(|_| "expected three hands")?;
+    for (i, hand) in hands.iter().enumerate() {
+        let size = if i == 0 { 12 } else { 10 };
+        if hand.len() != size {
+            return Err(format!("hand {} has {} cards instead of {}", i + 1, hand.len(), size));
+        }
+    }
+    let distinct: HashSet<&Card> = hands.iter().flatten().collect();
+    if distinct.len() != 32 {
+        return Err(String::from("a card is dealt twice"));
+    }
+    Ok(PresetDeal { name: name.trim().to_string(), hands })
+}
+
+pub fn format_deals(deals: &[PresetDeal]) -> String {
+    let mut text = String::from("# name: declarer | left | right\n");
+    for deal in deals {
+        let hands: Vec<String> = deal.hands
+            .iter()
+            .map(|hand| hand.iter().map(Card::to_string).collect::<Vec<_>>().join(" "))
+            .collect();
+        text.push_str(&format!("{}: {}\n", deal.name, hands.join(" | ")));
+    }
+    text
+}
+
+pub fn load_deals(path: impl AsRef<Path>) -> io::Result<Vec<PresetDeal>> {
+    parse_deals(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/* Random deals for a new deal file, the same seed gives the same file */
+pub fn generate_deals(count: usize, seed: u64) -> Vec<PresetDeal> {
+    let deck = card::fill_cards();
+    (0..count)
+        .map(|n| {
+            let id = DealId::from_match_seed(seed, n);
+            PresetDeal { name: id.to_string(), hands: id.deal(&deck, 0) }
+        })
+        .collect()
+}
+
+/*
+    Play every deal once at this table, entrant `(seat + table) % 3` sits at
+    `seat`. A human who quits ends the table with `GameError::Quit`
+*/
+pub fn play_table(
+    deals: &[PresetDeal],
+    table: usize,
+    names: &[String],
+    mut controllers: Vec<Box<dyn PlayerController>>,
+) -> Result<TableResult, GameError> {
+    let seating: [usize; 3] = [0, 1, 2].map(|seat| (seat + table) % 3);
+    controllers.rotate_left(table % 3);
+
+    let mut engine = GameEngine::new();
+    for (seat, entrant) in seating.iter().enumerate() {
+        engine.add_player(names[*entrant].clone(), seat == 0).expect("A table seats three players");
+    }
+
+    let mut result = TableResult { table, seating, contracts: vec![], scores: vec![] };
+    let mut before = [0; 3];
+    while let Some(decision) = engine.pending_decision() {
+        if decision == Decision::StartDeal {
+            let deal = match deals.get(engine.schedule.deals_played()) {
+                Some(deal) => deal,
+                None => break,
+            };
+            engine.set_next_hands(deal.hands.clone());
+            before = [0, 1, 2].map(|seat| engine.players[seat].score);
+            result.contracts.extend(engine.contract());
+        }
+        let events = controller::ask_until_accepted(&mut engine, &mut controllers, decision)?;
+        if events.contains(&Event::DealEnded) {
+            result.scores.push([0, 1, 2].map(|seat| engine.players[seat].score - before[seat]));
+        }
+    }
+    Ok(result)
+}
+
+/* Play the session at `tables` tables, three tables give every entrant every seat */
+pub fn run(deals: &[PresetDeal], entrants: &[Entrant], tables: usize) -> Result<Vec<TableResult>, String> {
+    if entrants.len() != 3 {
+        return Err(String::from("Duplicate needs three entrants"));
+    }
+    let names: Vec<String> = entrants.iter().map(|e| e.name.clone()).collect();
+
+    let mut results = vec![];
+    for table in 0..tables {
+        let mut controllers: Vec<Box<dyn PlayerController>> = vec![];
+        for entrant in entrants {
+            let controller: Box<dyn PlayerController> = if entrant.kind == "human" {
+                Box::new(StdinController::new())
+            } else {
+                bots::controller_by_name(&entrant.kind)
+                    .ok_or(format!("Unknown player kind: {}", entrant.kind))?
+            };
+            controllers.push(controller);
+        }
+        results.push(play_table(deals, table, &names, controllers).map_err(|e| e.to_string())?);
+    }
+    Ok(results)
+}
+
+/* Cross-table comparison of a session */
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub names: Vec<String>,
+    pub deals: Vec<String>,
+    pub contracts: Vec<Contract>,
+    pub scores: Vec<Vec<[i32; 3]>>, // per deal, per table, by seat
+    pub totals: Vec<i32>,           // per entrant
+    pub duplicate: Vec<f64>,        // per entrant, sum of differences to the seat average
+}
+
+/*
+    On every deal the score of a seat is compared with the average of that
+    seat over all tables, so only the difference the entrant made with the
+    same cards counts.
+*/
+pub fn compare(deals: &[PresetDeal], names: &[String], results: &[TableResult]) -> Report {
+    let played = results.iter().map(|r| r.scores.len()).min().unwrap_or(0);
+    let mut totals = vec![0; names.len()];
+    let mut duplicate = vec![0.0; names.len()];
+    let mut scores = vec![];
+
+    for deal in 0..played {
+        for seat in 0..3 {
+            let average = results.iter().map(|r| r.scores[deal][seat]).sum::<i32>() as f64 / results.len() as f64;
+            for result in results {
+                let entrant = result.seating[seat];
+                totals[entrant] += result.scores[deal][seat];
+                duplicate[entrant] += result.scores[deal][seat] as f64 - average;
+            }
+        }
+        scores.push(results.iter().map(|r| r.scores[deal]).collect());
+    }
+
+    let contracts = results.first().map(|r| r.contracts[..played].to_vec()).unwrap_or_default();
+
+    Report {
+        names: names.to_vec(),
+        deals: deals.iter().take(played).map(|d| d.name.clone()).collect(),
+        contracts,
+        scores,
+        totals,
+        duplicate,
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "<-------------------Duplicate------------------->")?;
+        for (deal, tables) in self.scores.iter().enumerate() {
+            let row: Vec<String> = tables
+                .iter()
+                .map(|s| format!("{:>4} {:>4} {:>4}", s[0], s[1], s[2]))
+                .collect();
+            writeln!(f, "{:<14} {} | {}", self.deals[deal], self.contracts[deal], row.join(" | "))?;
+        }
+
+        let mut order: Vec<usize> = (0..self.names.len()).collect();
+        order.sort_by(|a, b| self.duplicate[*b].total_cmp(&self.duplicate[*a]));
+        for (place, entrant) in order.iter().enumerate() {
+            writeln!(
+                f,
+                "{}. {}: {:+.1} (total {})",
+                place + 1,
+                self.names[*entrant],
+                self.duplicate[*entrant],
+                self.totals[*entrant]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::HeuristicBot;
+
+    #[test]
+    fn deal_files_round_trip() {
+        let deals = generate_deals(4, 9);
+        assert_eq!(parse_deals(&format_deals(&deals)), Ok(deals));
+    }
+
+    #[test]
+    fn broken_deal_files_are_rejected() {
+        let deals = generate_deals(1, 3);
+        let good = format_deals(&deals);
+        let line = good.lines().nth(1).unwrap();
+
+        let first = deals[0].hands[0][0].to_string();
+        let twice = line.replacen(&deals[0].hands[1][0].to_string(), &first, 1);
+        assert!(parse_deals(&twice).unwrap_err().contains("twice"));
+        assert!(parse_deals(&line.replacen(" | ", " ", 1)).is_err());
+        assert!(parse_deals("d1 7H 8H").is_err());
+        assert_eq!(parse_deals("# nothing\n\n"), Ok(vec![]));
+    }
+
+    #[test]
+    fn every_table_plays_the_same_cards() {
+        let deals = generate_deals(3, 5);
+        let names: Vec<String> = ["A", "B", "C"].iter().map(|n| n.to_string()).collect();
+        let results: Vec<TableResult> = (0..3)
+            .map(|table| {
+                let controllers: Vec<Box<dyn PlayerController>> =
+                    (0..3).map(|_| Box::new(HeuristicBot::new()) as Box<dyn PlayerController>).collect();
+                play_table(&deals, table, &names, controllers).unwrap()
+            })
+            .collect();
+
+        /* The same deterministic bot at every seat makes every table identical */
+        for result in &results {
+            assert_eq!(result.scores, results[0].scores);
+            assert_eq!(result.contracts, vec![Contract::King; 3]);
+        }
+        assert_eq!(results[1].seating, [1, 2, 0]);
+
+        let report = compare(&deals, &names, &results);
+        assert!(report.duplicate.iter().all(|d| d.abs() < 1e-9));
+        assert_eq!(report.totals.iter().sum::<i32>(), results.iter().flat_map(|r| &r.scores).flatten().sum::<i32>());
+    }
+}
@@ -1,26 +1,60 @@
-mod card;
-mod player;
-mod schedule;
-mod board;
-
+pub mod card;
+pub mod contract;
+pub mod player;
+pub mod schedule;
+pub mod board;
+pub mod deal;
+pub mod engine;
+pub mod log;
+pub mod notation;
+pub mod observation;
+pub mod replay;
+pub mod controller;
+pub mod command;
+pub mod save;
+#[cfg(test)]
+pub mod testing;
+
+/* Console front end driving the engine through stdin/stdout */
 pub mod main {
     /* Rust imports */
-    use core::panic;
     use std::io;
+    use std::path::PathBuf;
 
     /* Crate Imports */
     use super::card::Card;
-    use super::player::Player;
-    use super::schedule::Schedule;
-    use super::board::Board;
-    
+    use super::contract::Contract;
+    use super::controller::{self, PlayerController, StdinController};
+    use super::engine::{Decision, Event, GameError, GameEngine, RuleError, Standing};
+    use super::log::GameLog;
+    use super::observation::Observation;
+    use super::save;
+
     /* Main Struct of the Game */
     pub struct Game {
-        pub players: Vec<Player>,
-        pub deck: Vec<Card>,
-        pub board: Board,  // size 3
-        pub hidden_cards: Vec<Card>, // size 2
-        pub schedule: Schedule
+        pub engine: GameEngine,
+        pub controllers: Vec<Box<dyn PlayerController>>, // one per seat
+        pub autosave: Option<PathBuf>, // written after every trick
+        pub log: GameLog, // every event of the current match
+        pub log_file: Option<PathBuf>, // the log is appended to it after every action, see `match_log_file`
+        matches: usize, // started so far
+        match_log: Option<PathBuf>, // file of the match being played, None when it can not be written
+        pub print_events: bool, // off when a full-screen front end owns the terminal
+        pub spectator: bool // only bots play, every hand may be printed
+    }
+
+    /* What the players want once a match is over */
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum NextMatch {
+        Rematch,  // same seats again
+        NewMatch, // same players, every one moves one seat to the left
+        Quit,
+    }
+
+    impl Default for Game {
+        fn default() -> Self {
+            Self::new()
+        }
     }
 
     /* Implementation of the Game struct */
@@ -29,359 +63,233 @@ pub mod main {
         pub fn new() -> Self {
             println!("Game Commencing...");
             Game {
-                players: vec![],
-                hidden_cards: vec![],
-                deck: super::card::fill_cards(),
-                board: Board::new(),
-                schedule: Schedule::new()
+                engine: GameEngine::new(),
+                controllers: vec![],
+                autosave: Some(PathBuf::from(save::AUTOSAVE_FILE)),
+                log: GameLog::new(&GameEngine::new()),
+                log_file: None,
+                matches: 0,
+                match_log: None,
+                print_events: true,
+                spectator: false
             }
         }
 
-        /* Beginning of the game */
-        pub fn start_game(&mut self) -> () {
-            if !self.room_is_filled() {
-                panic!("Not enough players\n {}", self.players.len());
+        /* Continue a saved match, controllers given in seat order */
+        pub fn resume(engine: GameEngine, mut controllers: Vec<Box<dyn PlayerController>>) -> Self {
+            println!("Game Resuming...");
+            for (seat, controller) in controllers.iter_mut().enumerate() {
+                controller.seated(seat);
+            }
+            Game {
+                log: GameLog::new(&engine),
+                engine,
+                controllers,
+                autosave: Some(PathBuf::from(save::AUTOSAVE_FILE)),
+                log_file: None,
+                matches: 0,
+                match_log: None,
+                print_events: true,
+                spectator: false
             }
-            self.setup();
-            self.update();
-        }
-
-        pub fn setup(&mut self) -> () {
-            self.deal_cards();
-            self.let_player_hide_cards();
-            self.print_hidden_cards();
-            self.print_all_hands();
-        }
-
-        pub fn update(&mut self) -> () {
-            /* Game Loop */
-            loop {
-                Self::print_title(&self.schedule.get_game().to_string().to_owned());
-                self.play_round();
-                self.print_scores();
-                self.schedule.next_game();
-                self.setup();
-            } // End of the loop
         }
 
-        pub fn play_round(&mut self) -> () {
-            self.board.clean();
-            let mut turn: usize = 0; /* Tracks turns */
-            let mut gone_cards: Vec<Card> = vec![]; /* Tracks gone cards */
-            let mut current_suit: char = '0'; /* Tracks current suit */
-            let mut trump: char = '0';
-
-            if self.schedule.get_game() == '+' {
-                trump = self.plus_game_setup();
+        /* Beginning of the game */
+        pub fn start_game(&mut self) -> Result<(), GameError> {
+            if !self.engine.room_is_filled() {
+                return Err(RuleError::NotEnoughPlayers.into());
             }
-
-            /* Round loop */
-            loop {
-                /* Handle if player has not got any cards */
-                if self.players[turn].hand.len() == 0 {
-                    break;
+            self.matches += 1;
+            self.match_log = self.match_log_file();
+
+            /* A match already under way goes on in the log it was started in */
+            let under_way = self.engine.schedule.deals_played() > 0 ||
+                self.engine.pending_decision() != Some(Decision::StartDeal);
+            self.log = GameLog::new(&self.engine);
+            if let Some(path) = self.match_log.clone() {
+                let written = if under_way {
+                    GameLog::resume(&path, &self.engine).map(|log| self.log = log)
+                } else {
+                    self.log.save(&path)
+                };
+                if let Err(err) = written {
+                    println!("Can not write the game log to {}: {}", path.display(), err);
+                    self.match_log = None;
                 }
+            }
+            self.update()
+        }
 
-                /* Add drawn card and its owner to board hashmap */
-                let mut drawn_card: Card = self.players[turn].handle_player_turn();
-
-                /* Assign current suit */
-                if self.board.cards_on_board.len() == 0 {
-                    current_suit = drawn_card.suit;
-                }
+        /* Every match gets its own log: game.log for the first one, then game-2.log and so on */
+        fn match_log_file(&self) -> Option<PathBuf> {
+            let path = self.log_file.as_ref()?;
+            if self.matches <= 1 {
+                return Some(path.clone());
+            }
+            let stem = path.file_stem().map_or(String::new(), |s| s.to_string_lossy().into_owned());
+            let name = match path.extension() {
+                Some(extension) => format!("{}-{}.{}", stem, self.matches, extension.to_string_lossy()),
+                None => format!("{}-{}", stem, self.matches),
+            };
+            Some(path.with_file_name(name))
+        }
 
-                /* Check if drawn card is legal */
-                while self.is_illegal_king_move(&drawn_card, &turn) {
-                    println!("Illegal move! Try again");
-                    self.players[turn].print_hand();
-                    drawn_card = self.players[turn].handle_player_turn();
-                }
-                
-                /* Case during trump card */
-                if trump != '0' {
-                    /* 
-                        If current suit is not the same as drawn one and drawn suit is trump
-                        holds at least one card with current suit, it means that player used
-                        trump card illegally and we need to prevent it
-                        
-                        If drawn card's suit is not the same as the current one and player is holding
-                        trump, it is illegal move and we need to force player to draw trump card
-                    */    
-                    while   (current_suit != drawn_card.suit &&
-                            drawn_card.suit == trump &&
-                            self.contains_specific_suit(
-                                &self.players[turn].hand.clone(),
-                                current_suit
-                            )) 
-                            ||
-                            (current_suit != drawn_card.suit &&
-                                drawn_card.suit != trump &&
-                                self.contains_specific_suit(
-                                    &self.players[turn].hand.clone(),
-                                    trump
-                            ))
-                    {
-                        println!("Illegal move! Try again");
-                        self.players[turn].print_hand();
-                        drawn_card = self.players[turn].handle_player_turn();
+        /* Play until the match is over or a player quits, a player who saves is asked again */
+        pub fn update(&mut self) -> Result<(), GameError> {
+            /* Game Loop */
+            while let Some(decision) = self.engine.pending_decision() {
+                let events = match controller::ask_until_accepted(&mut self.engine, &mut self.controllers, decision) {
+                    Ok(events) => events,
+                    Err(GameError::Save(path)) => {
+                        self.save_for(decision, &path);
+                        continue;
+                    },
+                    Err(err) => return Err(err),
+                };
+                self.log.record(&events);
+                if let Some(path) = &self.match_log {
+                    if let Err(err) = GameLog::append(path, &events) {
+                        println!("Can not write the game log to {}: {}", path.display(), err);
                     }
-
                 }
-
-                /*
-                    If first suit of the first card on the board is not the same as players drawn card
-                    and player has the card of the suit, then it is an illegal move and let player draw again
-                */
-                while current_suit != drawn_card.suit && self.contains_specific_suit(&self.players[turn].hand.clone(), current_suit) {
-                    println!("Illegal move! Try again");
-                    self.players[turn].print_hand();
-                    drawn_card = self.players[turn].handle_player_turn();
-                }
-
-                /* Remove from players' hand */
-                self.players[turn].draw_card(drawn_card); 
-                
-                /* Update HashMap */
-                self.board.cards.insert(drawn_card, self.players[turn].clone());
-                
-                /* Update array*/
-                self.board.cards_on_board.push(drawn_card);
-
-                /* Update gone cards */
-                gone_cards.push(drawn_card);
-
-                match self.board.cards_on_board.len() {
-                    3 => {
-                        /* Case when there are 3 cards on the board */
-                        let biggest_card: &Card = &self.board.handle_board(trump);
-                        let taken_cards: Vec<Card> = self.board.get_cards_on_board();
-                        println!("Cards goes to {:?}", self.board.cards[biggest_card].username);
-                        
-                        let is_last_two = self.is_last_two();
-
-                        /*  Get final score of current type of game
-                            If it is NOT -1, then that means special cards occured on the board 
-                        */
-                        let final_score: i32 = self.board.handle_current_game(
-                            self.schedule.get_game(),
-                            &taken_cards,
-                            is_last_two
-                        );
-
-                        /* Handle user scores */
-                        if final_score != -1 {
-                            let round_has_ended = self.handle_specific_play(final_score, &biggest_card, &gone_cards);
-                            if round_has_ended {
-                                break;
-                            }
-                        }
-
-                        /* Handle changing turn value to the proper one */
-                        turn = self.players
-                        .iter()
-                        .position(|p| p.username == self.board.cards[biggest_card].username)
-                        .unwrap();
-
-                        self.print_all_hands();
-
-                        current_suit = '0';
-                        /* Clean up board */
-                        self.board.clean();
-                    },
-                    1 | 2 => {
-                        /* Case if only 1 or 2 players layed down their card */
-                        match turn {
-                            2 => { turn = 0; },
-                            0 | 1 => { turn += 1; },
-                            _ => { panic!("Panicked during checking turn"); }
-                        }
-                    },
-                    _ => {
-                        panic!("Panicked during checking cards.board length LENGTH: {}", self.board.cards.len());
+                for event in events {
+                    if self.print_events {
+                        self.print_event(&event);
+                    }
+                    if let Event::TrickWon { .. } = event {
+                        self.autosave();
                     }
                 }
-            } // End of loop
-            println!("Round has ended");
+            } // End of the loop
+            Ok(())
         }
 
-        pub fn plus_game_setup(&mut self) -> char {
-            let mut trump_announcement: String = String::new();
-            let announcer_idx = self.players
-            .iter()
-            .position(|p| p.is_his_play == true)
-            .unwrap();
-
-            let announcer = &self.players[announcer_idx];
-
-            println!("{}, choose trumps cards", announcer.username);
-
-            io::stdin().read_line(&mut trump_announcement).expect("Failed to read trump");
-            
-            let trump_announcement: char = match trump_announcement.trim().parse() {
-                Ok(ch) => ch,
-                Err(_) => panic!("Error during reading trumps card")
+        /* Save where the seat asked and tell it how that went */
+        fn save_for(&mut self, decision: Decision, path: &str) {
+            let text = match save::save_game(&self.engine, path) {
+                Ok(()) => format!("Game saved to {}", path),
+                Err(err) => format!("Could not save to {}: {}", path, err),
             };
-
-            if !vec!['H','D','C','S'].contains(&trump_announcement) {
-                panic!("Invalid Input")
+            if let Decision::HideCards { seat } | Decision::ChooseTrump { seat } | Decision::PlayCard { seat } = decision {
+                self.controllers[seat].inform(&text);
             }
-
-            trump_announcement
         }
 
-        pub fn handle_specific_play(&mut self, final_score: i32, biggest_card: &Card, gone_cards: &Vec<Card>) -> bool {
-            let p_idx = self.players
-                .iter()
-                .position(|p| p.username == self.board.cards[biggest_card].username)
-                .unwrap();
-
-                self.players[p_idx].score += final_score;
-
-                match self.schedule.get_game() {
-                    'K' => return true,
-                    'Q' => {
-                        let counter: u8 = self.count_specific_card(gone_cards, 'Q');
-                        println!("Gone queens: {}", counter);
-                        if counter == 4 { return true; }
-                    },
-                    'J' => {
-                        let counter: u8 = self.count_specific_card(gone_cards, 'J');
-                        if counter == 4 { return true; }
-                    },
-                    '2' => {
-                        println!("Last two has started!");
-                    },
-                    '+' => {
-                        println!("Plus Score goes to {}", self.players[p_idx].username);
-                    },
-                    _ => { println!("Another game") }
-                };
-                false
-        }
-
-        pub fn contains_specific_suit(&mut self, hand: &Vec<Card>, suit: char) -> bool {
-            for c in hand {
-                if c.suit == suit {
-                    return true;
+        fn autosave(&self) {
+            if let Some(path) = &self.autosave {
+                if let Err(err) = save::save_game(&self.engine, path) {
+                    println!("Autosave to {} failed: {}", path.display(), err);
                 }
             }
-            false
         }
 
-        pub fn count_specific_card(&mut self, gone_cards: &Vec<Card>, ch: char) -> u8 {
-            let mut counter = 0;
-            for c in gone_cards {
-                if c.rank.to_string() == ch.to_string() {
-                    counter += 1;
-                }
+        fn print_event(&self, event: &Event) {
+            let players = &self.engine.players;
+            match event {
+                Event::DealStarted { contract, .. } => {
+                    Self::print_title(contract.name());
+                    if let Some(id) = self.engine.deal_id() {
+                        println!("Deal ID: {}", id);
+                    }
+                },
+                Event::CardsDealt { .. } => {},
+                Event::CardsHidden { .. } if self.spectator => {
+                    self.print_hidden_cards();
+                    self.print_all_hands();
+                },
+                Event::CardsHidden { seat, .. } => {
+                    println!("{} hid two cards", players[*seat].username);
+                },
+                Event::TrumpChosen { trump, .. } => {
+                    println!("Trump is {}", trump);
+                },
+                Event::CardPlayed { card, .. } => {
+                    println!("Drawn {}", card);
+                },
+                Event::TrickWon { seat, card, .. } => {
+                    println!("Winner card is {}", card);
+                    println!("Cards goes to {}", players[*seat].username);
+                    if self.spectator {
+                        self.print_all_hands();
+                    }
+                },
+                Event::ScoreChanged { seat, delta, .. } => {
+                    println!("{} gets {}", players[*seat].username, delta);
+                },
+                Event::DealEnded => {
+                    println!("Round has ended");
+                    if let Some((declarer, [first, second])) = self.engine.talon().filter(|_| self.spectator) {
+                        println!("{} had hidden {} {}", players[declarer].username, first, second);
+                    }
+                    self.print_scores();
+                },
+                Event::MatchOver { standings } => {
+                    self.print_standings(standings);
+                },
             }
-            counter
         }
 
-        /* 
-            If it is kings game and player draw King Heart on the firstly,
-            that is illegal move and we need to prevent it.
-        */
-        pub fn is_illegal_king_move(&mut self, drawn_card: &Card, turn: &usize) -> bool {
-            if
-                self.board.cards_on_board.len() == 0 &&
-                self.schedule.get_game() == 'K' &&
-                drawn_card.suit == 'H' &&
-                drawn_card.rank == "K" &&
-                self.players[*turn].hand.iter().position(|c| c.suit != 'H') != None
-                {
-                    return true;
+        /* Ask at the console how to go on, end of input means quit */
+        pub fn ask_next_match(&self) -> NextMatch {
+            loop {
+                println!("Type rematch, new (same players, seats rotated) or quit");
+                let mut answer: String = String::new();
+                match io::stdin().read_line(&mut answer) {
+                    Ok(0) | Err(_) => return NextMatch::Quit,
+                    Ok(_) => {}
+                }
+                match answer.trim().to_ascii_lowercase().as_str() {
+                    "rematch" => return NextMatch::Rematch,
+                    "new" => return NextMatch::NewMatch,
+                    "quit" => return NextMatch::Quit,
+                    _ => println!("Unknown answer"),
                 }
-            false
+            }
         }
 
-        /* Shuffle and deal cards */
-        pub fn deal_cards(&mut self) -> () {
-            /* Shuffle cards and deal them to players */
-            let shuffled: [Vec<Card>; 3] = super::card::shuffle(&self.deck);
-            
-            for i in 0..3 {
-                self.players[i].hand = shuffled[i].clone();
+        /* Reset the engine for the chosen kind of match, false on quit */
+        pub fn prepare_next_match(&mut self, next: NextMatch) -> bool {
+            match next {
+                NextMatch::Rematch => {},
+                NextMatch::NewMatch => {
+                    self.engine.players.rotate_left(1);
+                    self.controllers.rotate_left(1);
+                    for (seat, controller) in self.controllers.iter_mut().enumerate() {
+                        controller.seated(seat);
+                    }
+                },
+                NextMatch::Quit => return false,
             }
+            self.engine.rematch();
+            true
         }
 
-        /* Let the main player choose 2 cards to hide */
-        pub fn let_player_hide_cards(&mut self) -> () {
-            let main_player = self.players
-            .iter()
-            .position(|c| c.is_his_play == true)
-            .unwrap();
-            self.players[main_player].print_hand();
-            
-            for _i in 0..2 {
-                let mut choice: String = String::new();
-                io::stdin().read_line(&mut choice).expect("Failed to read choice");
-                /* Validate user choice */
-                let choice: usize = match choice.trim().parse() {
-                    Ok(num) => num,
-                    Err(_) => panic!("Invalid input, Try again"),
-                };
-
-                if choice >= self.players[main_player].hand.len() {
-                    panic!("Invalid input");
-                }
-
-                /* Handle Removing */
-                /* Get the card that user wants to draw */
-                let card_to_remove = self.players[main_player].hand[choice];
-
-                /* Prohibit removing some cards */
-                match self.schedule.get_game() {
-                    'K' => {
-                        if card_to_remove.suit == 'H' && card_to_remove.rank == "K" {
-                            println!("You cant remove King Heart");
-                            self.let_player_hide_cards();
-                        }
-                    },
-                    'Q' => {
-                        if card_to_remove.rank == "Q" {
-                            println!("You cant remove Queen");
-                            self.let_player_hide_cards();
-                        }
-                    },
-                    'J' => {
-                        if card_to_remove.rank == "J" {
-                            println!("You cant remove Jack");
-                            self.let_player_hide_cards();
-                        }
-                    },
-                    'H' => {
-                        if card_to_remove.suit == 'H' {
-                            println!("You cant remove Heart");
-                            self.let_player_hide_cards();
-                        }
-                    },
-                    _ => {}
-                }
-
-                /* Print and remove the card from the hand array */
-                self.players[main_player].draw_card(card_to_remove);
-                
-                /* Add removed cards in hidden cards */
-                self.hidden_cards.push(card_to_remove);
-            }
+        /* Everything the seat may know, the only view a front end should show it */
+        pub fn observation(&self, seat: usize) -> Observation {
+            self.engine.observation(seat)
         }
 
-        pub fn is_last_two(&mut self) -> bool {
-            self.players[2].hand.len() < 2
+        /* Cards the seat may lay down right now */
+        pub fn legal_moves(&self, seat: usize) -> Vec<Card> {
+            self.engine.legal_moves(seat)
         }
 
-        /* Handle adding new player */
-        pub fn new_player(&mut self, username: String, is_his_play: bool) -> () {
-            if self.players.len() >= 3 {
-                panic!("Room has already been filled.");
-            } else {
-                self.players.push(Player::new(username, is_his_play));
-            }
+        /* Handle adding new player typing at this console */
+        pub fn new_player(&mut self, username: String, is_his_play: bool) -> Result<(), GameError> {
+            self.new_player_with_controller(username, is_his_play, Box::new(StdinController::new()))
         }
 
-        fn room_is_filled(&mut self) -> bool { 
-            &self.players.len() == &3
+        /* Handle adding new player driven by any controller */
+        pub fn new_player_with_controller(
+            &mut self,
+            username: String,
+            is_his_play: bool,
+            mut controller: Box<dyn PlayerController>
+        ) -> Result<(), GameError> {
+            self.engine.add_player(username, is_his_play)?;
+            controller.seated(self.controllers.len());
+            self.controllers.push(controller);
+            Ok(())
         }
 
         /* Handle print functions*/
@@ -389,35 +297,53 @@ pub mod main {
             println!("<-------------------{}------------------->", text);
         }
 
-        pub fn print_players(&mut self) -> () {
+        pub fn print_players(&self) {
             Self::print_title("Players");
-            for i in &self.players {
+            for i in &self.engine.players {
                 print!("<-{}-> ", i.username);
             }
             println!();
         }
 
-        pub fn print_all_hands(&mut self) -> () {
+        /* Every hand, only for spectators of a table without humans */
+        pub fn print_all_hands(&self) {
             Self::print_title("All Hands");
-            self.players[0].print_hand();
-            self.players[1].print_hand();
-            self.players[2].print_hand();
+            for player in &self.engine.players {
+                StdinController::print_hand(player);
+            }
         }
 
-        pub fn print_hidden_cards(&mut self) -> () {
+        pub fn print_hidden_cards(&self) {
             Self::print_title("Hidden Cards");
-            for i in &self.hidden_cards {
-                println!("{:?}", i);
+            if let Some((_, [first, second])) = self.engine.talon() {
+                println!("{} {}", first, second);
             }
             println!("__________________________________________\n");
         }
 
-        pub fn print_scores(&mut self) -> () {
+        pub fn print_standings(&self, standings: &[Standing]) {
+            Self::print_title("Final Standings");
+            for standing in standings {
+                let breakdown: Vec<String> = Contract::ALL
+                    .iter()
+                    .map(|c| format!("{} {}", c, standing.by_contract[c.index()]))
+                    .collect();
+                println!(
+                    "{}. {}: {} ({})",
+                    standing.place,
+                    self.engine.players[standing.seat].username,
+                    standing.score,
+                    breakdown.join(", ")
+                );
+            }
+        }
+
+        pub fn print_scores(&self) {
             Self::print_title("Scores");
-            println!("{}: {}", self.players[0].username, self.players[0].score);
-            println!("{}: {}", self.players[1].username, self.players[1].score);
-            println!("{}: {}", self.players[2].username, self.players[2].score);
+            for player in &self.engine.players {
+                println!("{}: {}", player.username, player.score);
+            }
         }
 
     }
-}
\ No newline at end of file
+}
@@ -1,52 +1,116 @@
-use std::collections::HashMap;
+use std::mem;
 
-use crate::game::card::Card;
-use crate::game::player::Player;
+use serde::{Deserialize, Serialize};
 
+use crate::game::card::{Card, Rank, Suit};
+use crate::game::contract::Contract;
+
+/* Tricks in a deal, every seat holds ten cards once the talon is hidden */
+pub const TRICKS: usize = 10;
+
+/* Cards of one trick in play order, each with the seat that laid it down */
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Trick {
+    pub leader: usize,
+    pub plays: Vec<(usize, Card)>,
+}
+
+/* The trick being played and the finished tricks of the deal */
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Board {
-    pub cards: HashMap<Card, Player>,
-    pub cards_on_board: Vec<Card>
+    pub trick: Trick,
+    pub history: Vec<Trick>, // oldest first
+}
+
+/*
+    Position in the trick of the card that takes it: the highest trump when a
+    trump was played, otherwise the highest card of the suit that was led.
+    Any number of cards works, None for an empty trick.
+*/
+pub fn trick_winner(trick: &[Card], trump: Option<Suit>) -> Option<usize> {
+    let lead = trick.first()?.suit;
+    let strength = |card: &Card| (Some(card.suit) == trump, card.suit == lead, card.rank);
+    trick
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, card)| strength(card))
+        .map(|(position, _)| position)
+}
+
+impl Trick {
+    pub fn new(leader: usize) -> Self {
+        Trick { leader, plays: vec![] }
+    }
+
+    pub fn cards(&self) -> Vec<Card> {
+        self.plays.iter().map(|(_, card)| *card).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.plays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plays.is_empty()
+    }
+
+    pub fn lead_suit(&self) -> Option<Suit> {
+        self.plays.first().map(|(_, card)| card.suit)
+    }
+
+    /* Seat taking the trick so far and its card */
+    pub fn winner(&self, trump: Option<Suit>) -> Option<(usize, Card)> {
+        trick_winner(&self.cards(), trump).map(|position| self.plays[position])
+    }
 }
 
 impl Board {
     pub fn new() -> Self {
-        Board {
-            cards: HashMap::new(),
-            cards_on_board: vec![]
-        }
+        Board::default()
+    }
+
+    /* Empty board for a new deal, `leader` plays the first card */
+    pub fn start(&mut self, leader: usize) {
+        self.trick = Trick::new(leader);
+        self.history.clear();
+    }
+
+    pub fn play(&mut self, seat: usize, card: Card) {
+        self.trick.plays.push((seat, card));
     }
 
-    pub fn handle_board(&mut self, trump: char) -> Card {
-        let mut c0: Card = self.cards_on_board[0];
-        let mut c1: Card = self.cards_on_board[1];
-        let mut c2: Card = self.cards_on_board[2];
-
-        let biggest_card: Card = self.compare_cards(
-            &mut c0, // current suit
-            &mut c1, 
-            &mut c2,
-            trump 
-        );
-        println!("Winner card is {:?}", biggest_card);
-        biggest_card
-    }
-
-    pub fn handle_current_game(&mut self, game_type: char, taken_cards: &Vec<Card>, is_last_two: bool) -> i32 {
-        match game_type {
-            'K' => self.handle_king_game(taken_cards),
-            '2' => self.handle_last_two(is_last_two),
-            'Q' => self.handle_queens_or_jacks_game(taken_cards, 'Q'),
-            'J' => self.handle_queens_or_jacks_game(taken_cards, 'J'),
-            'H' => self.handle_hearts_game(taken_cards),
-            'V' => self.handle_vziatk_game(),
-            '+' => self.handle_plus_game(),
-            _   => panic!("Invalid game type")
+    /* File the full trick away, its winner leads the next one */
+    pub fn finish_trick(&mut self, winner: usize) {
+        let done = mem::replace(&mut self.trick, Trick::new(winner));
+        self.history.push(done);
+    }
+
+    /* Every card played this deal, in order */
+    pub fn played_cards(&self) -> Vec<Card> {
+        self.history.iter().chain([&self.trick]).flat_map(Trick::cards).collect()
+    }
+
+    /* Whether the trick being played is one of the last two of the deal */
+    pub fn is_last_two(&self) -> bool {
+        self.history.len() + 2 >= TRICKS
+    }
+
+    pub fn handle_current_game(&mut self, contract: Contract, taken_cards: &[Card], is_last_two: bool) -> i32 {
+        match contract {
+            Contract::King => self.handle_king_game(taken_cards),
+            Contract::LastTwo => self.handle_last_two(is_last_two),
+            Contract::Queens => self.handle_queens_or_jacks_game(taken_cards, Rank::Queen),
+            Contract::Jacks => self.handle_queens_or_jacks_game(taken_cards, Rank::Jack),
+            Contract::Hearts => self.handle_hearts_game(taken_cards),
+            Contract::Tricks => self.handle_vziatk_game(),
+            Contract::Plus => self.handle_plus_game(),
         }
     }
 
-    pub fn handle_king_game(&mut self, taken_cards: &Vec<Card>) -> i32 {
+    pub fn handle_king_game(&mut self, taken_cards: &[Card]) -> i32 {
         for card in taken_cards {
-            if card.suit == 'H' && card.rank == "K" {
+            if card.suit == Suit::Hearts && card.rank == Rank::King {
                 return -40;
             }
         }
@@ -55,24 +119,24 @@ impl Board {
 
     pub fn handle_last_two(&mut self, is_last_two: bool) -> i32 {
         if is_last_two {
-            return -20;
+            -20
         } else {
-            return -1;
+            -1
         }
     }
 
-    pub fn handle_queens_or_jacks_game(&mut self, taken_cards: &Vec<Card>, game_type: char) -> i32 {
+    pub fn handle_queens_or_jacks_game(&mut self, taken_cards: &[Card], rank: Rank) -> i32 {
         for card in taken_cards {
-            if card.rank.to_string() == game_type.to_string() {
+            if card.rank == rank {
                 return -10;
             }
         }
-        return -1;
+        -1
     }
 
-    pub fn handle_hearts_game(&mut self, taken_cards: &Vec<Card>) -> i32 {
+    pub fn handle_hearts_game(&mut self, taken_cards: &[Card]) -> i32 {
         for card in taken_cards {
-            if card.suit == 'H' {
+            if card.suit == Suit::Hearts {
                 return -5;
             }
         }
@@ -86,75 +150,91 @@ impl Board {
     pub fn handle_plus_game(&mut self) -> i32 {
         4
     }
+}
 
-    pub fn compare_cards(&mut self, c1: &mut Card, c2: &mut Card, c3: &mut Card, trump: char) -> Card {
-        let c1_rank = c1.get_rank_idx();
-        let c2_rank = c2.get_rank_idx();
-        let c3_rank = c3.get_rank_idx();
-
-        if trump != '0' {
-            /* If there is no trump or all of them are trumps */
-            if c1.suit == c2.suit && c2.suit == c3.suit {
-                if c1_rank > c2_rank && c1_rank > c3_rank {
-                    return *c1;
-                } else if c2_rank > c1_rank && c2_rank > c3_rank {
-                    return *c2;
-                } else {
-                    return *c3;
-                }
-            } 
-            /* If there is only one trump */
-            else if c1.suit == trump && c2.suit != trump && c3.suit != trump { return *c1 }
-            else if c2.suit == trump && c1.suit != trump && c3.suit != trump { return *c2 }
-            else if c3.suit == trump && c1.suit != trump && c2.suit != trump { return *c3 }
-
-            /* If there are two trumps */
-            else if c1.suit == trump && c2.suit == trump && c3.suit != trump { 
-                return self.biggest(&c1, &c2);
-            }
-            else if c1.suit == trump && c3.suit == trump && c2.suit != trump { 
-                return self.biggest(&c1, &c3);
-            }
-            else if c1.suit == trump && c2.suit == trump && c3.suit != trump { 
-                return self.biggest(&c1, &c2);
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::card;
 
-        if c1.suit == c2.suit && c2.suit == c3.suit {
-            if c1_rank > c2_rank && c1_rank > c3_rank {
-                return *c1;
-            } else if c2_rank > c1_rank && c2_rank > c3_rank {
-                return *c2;
-            } else {
-                return *c3;
-            }
-        } else if c1.suit != c3.suit && c1.suit == c2.suit {
-            self.biggest(&c1, &c2)
-        }  else if c1.suit != c2.suit && c1.suit == c3.suit {
-            self.biggest(&c1, &c3)
+    /* Straight from the rules: trumps beat everything, then only the lead suit counts */
+    fn expected_winner(trick: &[Card], trump: Option<Suit>) -> Card {
+        let trumps: Vec<Card> = trick.iter().filter(|c| Some(c.suit) == trump).copied().collect();
+        let candidates = if trumps.is_empty() {
+            trick.iter().filter(|c| c.suit == trick[0].suit).copied().collect()
         } else {
-            return *c1;
-        }
+            trumps
+        };
+        *candidates.iter().max_by_key(|c| c.rank).unwrap()
     }
 
-    pub fn biggest(&mut self, c1: &Card, c2: &Card) -> Card {
-        if c1.rank > c2.rank {
-            return *c1;
-        } else {
-            return *c2;
+    fn trumps() -> [Option<Suit>; 5] {
+        [None, Some(Suit::Hearts), Some(Suit::Diamonds), Some(Suit::Spades), Some(Suit::Clubs)]
+    }
+
+    #[test]
+    fn tricks_keep_seats_order_and_history() {
+        let card = |c: &str| -> Card { c.parse().unwrap() };
+        let mut board = Board::new();
+        board.start(2);
+        for (seat, c) in [(2, "9S"), (0, "AS"), (1, "7H")] {
+            board.play(seat, card(c));
         }
+        assert_eq!(board.trick.lead_suit(), Some(Suit::Spades));
+        assert_eq!(board.trick.winner(None), Some((0, card("AS"))));
+        assert_eq!(board.trick.winner(Some(Suit::Hearts)), Some((1, card("7H"))));
+
+        board.finish_trick(0);
+        board.play(0, card("KD"));
+        assert_eq!(board.history[0].leader, 2);
+        assert_eq!(board.trick.leader, 0);
+        assert_eq!(board.played_cards(), vec![card("9S"), card("AS"), card("7H"), card("KD")]);
+
+        assert!(!board.is_last_two());
+        board.history.resize(TRICKS - 2, Trick::new(0));
+        assert!(board.is_last_two());
+        board.start(1);
+        assert!(board.history.is_empty() && board.trick.is_empty());
     }
 
-    pub fn get_cards_on_board(&mut self) -> Vec<Card> {
-        let mut result: Vec<Card> = vec![];
-        for (key, _value) in &self.cards {
-            result.push(*key);
+    #[test]
+    fn every_three_card_trick() {
+        let deck = card::fill_cards();
+        for a in &deck {
+            for b in deck.iter().filter(|c| *c != a) {
+                for c in deck.iter().filter(|c| *c != a && *c != b) {
+                    let trick = [*a, *b, *c];
+                    for trump in trumps() {
+                        let position = trick_winner(&trick, trump).unwrap();
+                        assert_eq!(trick[position], expected_winner(&trick, trump), "{:?} trump {:?}", trick, trump);
+                    }
+                }
+            }
         }
-        result
     }
 
-    pub fn clean(&mut self) -> () {
-        self.cards.clear();
-        self.cards_on_board.clear();
+    #[test]
+    fn four_card_tricks() {
+        let deck = card::fill_cards();
+        let spades: Vec<Card> = deck.iter().filter(|c| c.suit == Suit::Spades).copied().collect();
+        let others: Vec<Card> = deck.iter().filter(|c| c.suit != Suit::Spades).copied().collect();
+        /* Spades led, every combination of the other three cards from a reduced deck */
+        for lead in &spades {
+            for b in others.iter().step_by(3) {
+                for c in others.iter().chain(&spades).filter(|c| *c != b && *c != lead) {
+                    for d in deck.iter().filter(|d| *d != b && *d != c && *d != lead) {
+                        let trick = [*lead, *b, *c, *d];
+                        for trump in trumps() {
+                            let position = trick_winner(&trick, trump).unwrap();
+                            assert_eq!(trick[position], expected_winner(&trick, trump));
+                        }
+                    }
+                }
+            }
+        }
+        let trick: Vec<Card> = ["7S", "AH", "8S", "7H"].iter().map(|c| c.parse().unwrap()).collect();
+        assert_eq!(trick_winner(&trick, None), Some(2));
+        assert_eq!(trick_winner(&trick, Some(Suit::Hearts)), Some(1));
+        assert_eq!(trick_winner(&[], None), None);
     }
-}
\ No newline at end of file
+}
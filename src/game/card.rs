@@ -1,55 +1,203 @@
 extern crate rand;
 
+use std::fmt;
+use std::str::FromStr;
+
 use rand::Rng;
 
-const SUITS: [char; 4] = ['H', 'D', 'S', 'C'];
-const RANKS: [&str; 8] = ["7", "8", "9", "10", "J", "Q", "K", "A"];
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Suit {
+    Hearts,
+    Diamonds,
+    Spades,
+    Clubs,
+}
+
+/* Ranks are declared from the lowest to the highest, so `Ord` compares card strength */
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Rank {
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Hearts, Suit::Diamonds, Suit::Spades, Suit::Clubs];
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    /* Letter used in card notation */
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::Hearts => 'H',
+            Suit::Diamonds => 'D',
+            Suit::Spades => 'S',
+            Suit::Clubs => 'C',
+        }
+    }
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::Seven, Rank::Eight, Rank::Nine, Rank::Ten,
+        Rank::Jack, Rank::Queen, Rank::King, Rank::Ace,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rank::Seven => "7",
+            Rank::Eight => "8",
+            Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+            Rank::Ace => "A",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Card {
-    pub suit: char,
-    pub rank: &'static str,
+    pub suit: Suit,
+    pub rank: Rank,
 }
 
 impl Card {
-    pub fn new(suit: char, rank: &'static str) -> Self {
+    pub fn new(suit: Suit, rank: Rank) -> Self {
         Card { suit, rank }
     }
+}
+
+/* Error returned when a suit, rank or card can not be read from notation */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseCardError {
+    pub input: String,
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is not a valid card, suit or rank", self.input)
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/* Rank followed by suit, e.g. `10H` or `KS` */
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        /* Letter, symbol or name */
+        match s.trim().to_ascii_uppercase().as_str() {
+            "H" | "♥" | "♡" | "HEARTS" => Ok(Suit::Hearts),
+            "D" | "♦" | "♢" | "DIAMONDS" => Ok(Suit::Diamonds),
+            "S" | "♠" | "♤" | "SPADES" => Ok(Suit::Spades),
+            "C" | "♣" | "♧" | "CLUBS" => Ok(Suit::Clubs),
+            _ => Err(ParseCardError { input: s.to_string() }),
+        }
+    }
+}
 
-    pub fn get_rank_idx(&mut self) -> usize {
-        RANKS
-        .iter()
-        .position(|r| *r == self.rank)
-        .unwrap()
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        Rank::ALL
+            .iter()
+            .find(|r| r.as_str() == upper)
+            .copied()
+            .ok_or(ParseCardError { input: s.to_string() })
     }
 }
 
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let error = || ParseCardError { input: s.to_string() };
+
+        /* The suit is always the last character, the rest is the rank */
+        let split = trimmed.char_indices().last().ok_or_else(error)?.0;
+        let rank: Rank = trimmed[..split].parse().map_err(|_| error())?;
+        let suit: Suit = trimmed[split..].parse().map_err(|_| error())?;
+        Ok(Card::new(suit, rank))
+    }
+}
+
+/* Serialize through the card notation, e.g. "10H", so saved games and messages stay readable */
+macro_rules! serde_as_notation {
+    ($($t:ty),*) => {$(
+        impl serde::Serialize for $t {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let text = String::deserialize(deserializer)?;
+                text.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+pub(crate) use serde_as_notation;
+
+serde_as_notation!(Suit, Rank, Card);
+
 pub fn fill_cards() -> Vec<Card> {
     let mut deck: Vec<Card> = vec![];
-    for suit in SUITS {
-        for rank in RANKS {
+    for suit in Suit::ALL {
+        for rank in Rank::ALL {
             deck.push(Card::new(suit, rank));
         }
     }
     deck
 }
 
-pub fn shuffle(d: &Vec<Card>) -> [Vec<Card>; 3] {
+/*
+    Deal ten cards to everyone and the two extra cards to the declarer.
+    Hands are dealt relative to the declarer, so the same random sequence
+    gives the declarer and each opponent the same cards whoever declares.
+*/
+pub fn shuffle(d: &[Card], declarer: usize, rng: &mut impl Rng) -> [Vec<Card>; 3] {
     /* Clone our deck into a new variable to avoid changing original deck */
-    let mut deck = d.clone();
+    let mut deck = d.to_vec();
 
     /* Make 3-length array of vectors to handle cards for each player */
     let mut shuffled: [Vec<Card>; 3] = [vec![], vec![], vec![]];
 
     for _i in 0..10 {
-        for j in 0..3 {
-            /* Handle Randomizing Cards */
-            let mut rng = rand::thread_rng();
+        for hand in shuffled.iter_mut() {
+            /* Take a random card from the vector */
             let random_number = rng.gen_range(0..deck.len());
-            
-            /* Take value from the vector and return it */
             let random_card = deck.remove(random_number);
-            shuffled[j].push(random_card);
+            hand.push(random_card);
         }
     }
 
@@ -57,5 +205,38 @@ pub fn shuffle(d: &Vec<Card>) -> [Vec<Card>; 3] {
     shuffled[0].push(deck[0]);
     shuffled[0].push(deck[1]);
 
+    /* First hand goes to the declarer, then around the table */
+    shuffled.rotate_right(declarer);
     shuffled
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_are_ordered_by_strength() {
+        assert!(Rank::Ten > Rank::Seven);
+        assert!(Rank::Ace > Rank::King);
+        assert!(Rank::Jack > Rank::Ten);
+    }
+
+    #[test]
+    fn cards_round_trip_through_notation() {
+        for card in fill_cards() {
+            assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+        }
+        assert_eq!("10h".parse::<Card>(), Ok(Card::new(Suit::Hearts, Rank::Ten)));
+        assert_eq!("KS".parse::<Card>(), Ok(Card::new(Suit::Spades, Rank::King)));
+        assert_eq!("q♠".parse::<Card>(), Ok(Card::new(Suit::Spades, Rank::Queen)));
+        assert_eq!("10♥".parse::<Card>(), Ok(Card::new(Suit::Hearts, Rank::Ten)));
+        assert_eq!("clubs".parse::<Suit>(), Ok(Suit::Clubs));
+    }
+
+    #[test]
+    fn bad_notation_is_rejected() {
+        for input in ["", "H", "1H", "KX", "11S", "K♠S"] {
+            assert!(input.parse::<Card>().is_err(), "{} parsed", input);
+        }
+    }
+}
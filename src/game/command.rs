@@ -0,0 +1,183 @@
+/*
+    What a human can type at the console prompt: a card in notation (`KH`,
+    `10s`, `q♠`), its position in the hand as before, a suit when naming
+    trumps, or a command that looks at the game without playing. Everything a
+    command shows comes from the seat's `Observation`.
+*/
+
+use std::str::FromStr;
+
+use crate::bots::HeuristicBot;
+use crate::game::card::{Card, Suit};
+use crate::game::controller::PlayerController;
+use crate::game::engine::{Decision, GameError, RuleError};
+use crate::game::observation::Observation;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    Index(usize),
+    Card(Card),
+    Suit(Suit),
+    Hand,
+    Trick,
+    History,
+    Score,
+    Hint,
+    Rules,
+    Help,
+    Save(Option<String>),
+    Quit,
+}
+
+pub const HELP: &str = "\
+Play a card by its notation (KH, 10s, q♠) or its number in the hand, name trumps by suit (H, ♥ or hearts)
+  hand     your cards with their numbers
+  trick    cards on the table
+  history  tricks taken this deal
+  score    scores of the match
+  hint     what the computer would do
+  rules    rules of the current game
+  save [file], quit";
+
+pub const RULES: &str = "\
+Follow the suit of the first card when you can, in Plus play a trump when you can not.
+The declarer hides two cards before the deal, the cards a game punishes only when nothing else is left.
+King: do not take the King Heart, which can not be led while you have other cards.
+Last Two: do not take the last two tricks. Plus: take tricks, the declarer names trumps.
+Queens, Jacks, Hearts: do not take those cards. Tricks: do not take tricks at all.";
+
+impl FromStr for Command {
+    type Err = GameError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let first = match words.first() {
+            Some(word) => word.to_lowercase(),
+            None => return Err(GameError::UnknownCommand(String::new())),
+        };
+        let command = match (first.as_str(), &words[1..]) {
+            ("save", []) => Command::Save(None),
+            ("save", [path]) => Command::Save(Some(path.to_string())),
+            ("hand", []) => Command::Hand,
+            ("trick", []) => Command::Trick,
+            ("history", []) => Command::History,
+            ("score", []) => Command::Score,
+            ("hint", []) => Command::Hint,
+            ("rules", []) => Command::Rules,
+            ("help", []) => Command::Help,
+            ("quit", []) => Command::Quit,
+            (word, []) => {
+                if let Ok(index) = word.parse() {
+                    Command::Index(index)
+                } else if let Ok(card) = word.parse() {
+                    Command::Card(card)
+                } else if let Ok(suit) = word.parse() {
+                    Command::Suit(suit)
+                } else {
+                    return Err(GameError::UnknownCommand(words[0].to_string()));
+                }
+            },
+            _ => return Err(GameError::UnknownCommand(line.trim().to_string())),
+        };
+        Ok(command)
+    }
+}
+
+impl Command {
+    /* Text the player typed for a card, position or suit */
+    fn input(&self) -> String {
+        match self {
+            Command::Index(index) => index.to_string(),
+            Command::Card(card) => card.to_string(),
+            Command::Suit(suit) => suit.to_string(),
+            other => format!("{:?}", other).to_lowercase(),
+        }
+    }
+
+    /* The card of `hand` the command stands for */
+    pub fn card_in(&self, hand: &[Card]) -> Result<Card, GameError> {
+        match self {
+            Command::Index(index) => hand
+                .get(*index)
+                .copied()
+                .ok_or(GameError::InvalidIndex { input: index.to_string(), len: hand.len() }),
+            Command::Card(card) if hand.contains(card) => Ok(*card),
+            Command::Card(_) => Err(GameError::Rule(RuleError::CardNotInHand)),
+            other => Err(GameError::ExpectedCard(other.input())),
+        }
+    }
+
+    pub fn trump(&self) -> Result<Suit, GameError> {
+        match self {
+            Command::Suit(suit) => Ok(*suit),
+            other => Err(GameError::UnknownTrump(other.input())),
+        }
+    }
+}
+
+/* Answer to a command that does not play, None for a card, position or suit */
+pub fn answer(seen: &Observation, command: &Command) -> Option<String> {
+    let text = match command {
+        Command::Hand => seen.hand_line(),
+        Command::Trick => seen.trick_line(),
+        Command::History if seen.tricks.is_empty() => String::from("No trick was taken yet this deal"),
+        Command::History => seen.history_lines().join("\n"),
+        Command::Score => seen.score_line(),
+        Command::Hint => hint(seen),
+        Command::Rules => String::from(RULES),
+        Command::Help => String::from(HELP),
+        _ => return None,
+    };
+    Some(text)
+}
+
+/* What the heuristic bot would answer in the seat's place */
+pub fn hint(seen: &Observation) -> String {
+    let mut bot = HeuristicBot::new();
+    let hint = match seen.decision {
+        Some(Decision::PlayCard { seat }) if seat == seen.seat => bot.choose_card(seen).map(|c| format!("Try {}", c)),
+        Some(Decision::ChooseTrump { seat }) if seat == seen.seat => bot.choose_trump(seen).map(|t| format!("Try {}", t)),
+        Some(Decision::HideCards { seat }) if seat == seen.seat => bot
+            .choose_cards_to_hide(seen)
+            .map(|[first, second]| format!("Try hiding {} and {}", first, second)),
+        _ => Ok(String::from("Nothing to decide right now")),
+    };
+    hint.unwrap_or_else(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::card::Rank;
+
+    #[test]
+    fn cards_positions_suits_and_commands() {
+        let queen = Card::new(Suit::Spades, Rank::Queen);
+        assert_eq!("q♠".parse(), Ok(Command::Card(queen)));
+        assert_eq!(" 10s\n".parse(), Ok(Command::Card(Card::new(Suit::Spades, Rank::Ten))));
+        assert_eq!("KH".parse(), Ok(Command::Card(Card::new(Suit::Hearts, Rank::King))));
+        assert_eq!("3".parse(), Ok(Command::Index(3)));
+        assert_eq!("hearts".parse(), Ok(Command::Suit(Suit::Hearts)));
+        assert_eq!("HISTORY".parse(), Ok(Command::History));
+        assert_eq!("save x.json".parse(), Ok(Command::Save(Some(String::from("x.json")))));
+
+        assert_eq!("hnad".parse::<Command>(), Err(GameError::UnknownCommand(String::from("hnad"))));
+        assert!("hand 3".parse::<Command>().is_err());
+        assert!("".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn commands_pick_from_the_hand() {
+        let hand = [Card::new(Suit::Hearts, Rank::Seven), Card::new(Suit::Clubs, Rank::Ace)];
+        assert_eq!(Command::Index(1).card_in(&hand), Ok(hand[1]));
+        assert_eq!("7h".parse::<Command>().unwrap().card_in(&hand), Ok(hand[0]));
+        assert_eq!(
+            Command::Index(2).card_in(&hand),
+            Err(GameError::InvalidIndex { input: String::from("2"), len: 2 })
+        );
+        assert!(Command::Card(Card::new(Suit::Spades, Rank::Ace)).card_in(&hand).is_err());
+        assert_eq!(Command::Suit(Suit::Clubs).card_in(&hand), Err(GameError::ExpectedCard(String::from("C"))));
+        assert_eq!(Command::Suit(Suit::Clubs).trump(), Ok(Suit::Clubs));
+        assert!(Command::Index(0).trump().is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::game::card::{serde_as_notation, Card, Rank, Suit};
+
+/* The seven kinds of game played during a match of King */
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Contract {
+    King,    // 'K' do not take the King Heart
+    LastTwo, // '2' do not take the last two tricks
+    Plus,    // '+' take as many tricks as possible, declarer names trumps
+    Queens,  // 'Q' do not take queens
+    Jacks,   // 'J' do not take jacks
+    Tricks,  // 'V' do not take tricks (vziatka)
+    Hearts,  // 'H' do not take hearts
+}
+
+impl Contract {
+    pub const ALL: [Contract; 7] = [
+        Contract::King, Contract::LastTwo, Contract::Plus, Contract::Queens,
+        Contract::Jacks, Contract::Tricks, Contract::Hearts,
+    ];
+
+    /* Short code used in the schedule and in notation */
+    pub fn code(&self) -> char {
+        match self {
+            Contract::King => 'K',
+            Contract::LastTwo => '2',
+            Contract::Plus => '+',
+            Contract::Queens => 'Q',
+            Contract::Jacks => 'J',
+            Contract::Tricks => 'V',
+            Contract::Hearts => 'H',
+        }
+    }
+
+    /* Position in `Contract::ALL` */
+    pub fn index(&self) -> usize {
+        Contract::ALL.iter().position(|c| c == self).unwrap()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Contract::King => "King",
+            Contract::LastTwo => "Last Two",
+            Contract::Plus => "Plus",
+            Contract::Queens => "Queens",
+            Contract::Jacks => "Jacks",
+            Contract::Tricks => "Tricks",
+            Contract::Hearts => "Hearts",
+        }
+    }
+
+    /* Cards whose taking costs points in this game */
+    pub fn is_penalty_card(&self, card: &Card) -> bool {
+        match self {
+            Contract::King => card.suit == Suit::Hearts && card.rank == Rank::King,
+            Contract::Queens => card.rank == Rank::Queen,
+            Contract::Jacks => card.rank == Rank::Jack,
+            Contract::Hearts => card.suit == Suit::Hearts,
+            _ => false,
+        }
+    }
+
+    /* Only the '+' game is played with trumps */
+    pub fn has_trump(&self) -> bool {
+        *self == Contract::Plus
+    }
+}
+
+impl fmt::Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for Contract {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().to_ascii_uppercase();
+        Contract::ALL
+            .iter()
+            .find(|c| c.code().to_string() == code || c.name().to_ascii_uppercase() == code)
+            .copied()
+            .ok_or(format!("'{}' is not a contract", s))
+    }
+}
+
+serde_as_notation!(Contract);
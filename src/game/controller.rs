@@ -0,0 +1,385 @@
+/*
+    Whoever sits at a seat: a human at the keyboard, a bot or a remote client.
+    The console game asks the seat's controller every time the engine is waiting
+    for that seat, and reports rejected answers back so it can try again.
+
+    A controller never gets the engine, only the `Observation` of its own seat
+    and every event as that seat may see it, so it can not look at other hands.
+
+    A controller whose player leaves answers `GameError::Quit`, one who wants
+    the match saved answers `GameError::Save`. Both travel up to the program,
+    which saves the match itself and restores the terminal or asks again.
+*/
+
+use std::io::{self, Write};
+
+use crate::game::card::{Card, Suit};
+use crate::game::command::{self, Command};
+use crate::game::engine::{Action, Decision, Event, GameError, GameEngine};
+use crate::game::observation::{self, Observation};
+use crate::game::player::Player;
+use crate::game::save;
+
+pub trait PlayerController {
+    /* Card to lay down on the board, one of `seen.legal_moves` */
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError>;
+
+    /* Trump suit for the '+' game */
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError>;
+
+    /* Two cards the declarer hides from the 12-card hand, one of `seen.discard_pairs` */
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError>;
+
+    /* Called when the engine refused the last answer of this seat */
+    fn rejected(&mut self, _seen: &Observation, _error: GameError) {}
+
+    /* Called for every event of the game, whoever caused it */
+    fn notify(&mut self, _seen: &Observation, _event: &Event) {}
+
+    /* Called when the controller takes a seat or is moved to another one */
+    fn seated(&mut self, _seat: usize) {}
+
+    /* A note for the player from the program, like where the match was saved */
+    fn inform(&mut self, _text: &str) {}
+}
+
+impl<T: PlayerController + ?Sized> PlayerController for Box<T> {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        (**self).choose_card(seen)
+    }
+
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError> {
+        (**self).choose_trump(seen)
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        (**self).choose_cards_to_hide(seen)
+    }
+
+    fn rejected(&mut self, seen: &Observation, error: GameError) {
+        (**self).rejected(seen, error)
+    }
+
+    fn notify(&mut self, seen: &Observation, event: &Event) {
+        (**self).notify(seen, event)
+    }
+
+    fn seated(&mut self, seat: usize) {
+        (**self).seated(seat)
+    }
+
+    fn inform(&mut self, text: &str) {
+        (**self).inform(text)
+    }
+}
+
+/*
+    Ask the seat's controller until the engine accepts its answer, then tell
+    every controller what its seat saw. An error of the controller itself,
+    like a player who quits, ends the asking and is returned as it is
+*/
+pub fn ask_until_accepted<C: PlayerController>(
+    engine: &mut GameEngine,
+    controllers: &mut [C],
+    decision: Decision,
+) -> Result<Vec<Event>, GameError> {
+    let events = loop {
+        let (seat, action) = match decision {
+            Decision::StartDeal => {
+                break engine
+                    .apply(Action::StartDeal)
+                    .expect("Engine refused to start a deal");
+            },
+            Decision::HideCards { seat } => {
+                let cards = controllers[seat].choose_cards_to_hide(&engine.observation(seat))?;
+                (seat, Action::HideCards { seat, cards })
+            },
+            Decision::ChooseTrump { seat } => {
+                let trump = controllers[seat].choose_trump(&engine.observation(seat))?;
+                (seat, Action::ChooseTrump { seat, trump })
+            },
+            Decision::PlayCard { seat } => {
+                let card = controllers[seat].choose_card(&engine.observation(seat))?;
+                (seat, Action::PlayCard { seat, card })
+            },
+        };
+
+        match engine.apply(action) {
+            Ok(events) => break events,
+            Err(err) => controllers[seat].rejected(&engine.observation(seat), err),
+        }
+    };
+
+    let views: Vec<Observation> = (0..controllers.len()).map(|seat| engine.observation(seat)).collect();
+    for event in &events {
+        for (seat, controller) in controllers.iter_mut().enumerate() {
+            controller.notify(&views[seat], &observation::redact(event, seat));
+        }
+    }
+    Ok(events)
+}
+
+/* Human player typing at the console */
+#[derive(Default)]
+pub struct StdinController;
+
+impl StdinController {
+    pub fn new() -> Self {
+        StdinController
+    }
+
+    /* Next line from stdin, the player quits when nobody can answer any more */
+    fn read_line() -> Result<String, GameError> {
+        let mut line: String = String::new();
+        if let Ok(0) | Err(_) = io::stdin().read_line(&mut line) {
+            println!("Input closed, leaving the game");
+            return Err(GameError::Quit);
+        }
+        Ok(line)
+    }
+
+    /* Next card, position or suit, every other command but save and quit is answered right here */
+    fn read_choice(seen: &Observation) -> Result<Command, GameError> {
+        loop {
+            let command = match Self::read_line()?.parse::<Command>() {
+                Ok(command) => command,
+                Err(error) => {
+                    println!("{}", error);
+                    continue;
+                },
+            };
+            match command {
+                Command::Index(_) | Command::Card(_) | Command::Suit(_) => return Ok(command),
+                Command::Save(path) => {
+                    return Err(GameError::Save(path.unwrap_or_else(|| String::from(save::AUTOSAVE_FILE))));
+                },
+                Command::Quit => return Err(GameError::Quit),
+                other => {
+                    if let Some(text) = command::answer(seen, &other) {
+                        println!("{}", text);
+                    }
+                },
+            }
+        }
+    }
+
+    /* Ask until the answer fits, every mistake is explained and asked again */
+    fn read_until_ok<T>(
+        seen: &Observation,
+        pick: impl Fn(&Command) -> Result<T, GameError>,
+    ) -> Result<T, GameError> {
+        loop {
+            match pick(&Self::read_choice(seen)?) {
+                Ok(value) => return Ok(value),
+                Err(error) => println!("{}, try again", error),
+            }
+        }
+    }
+
+    /* Cards in notation with the number that also picks them */
+    pub fn print_hand(player: &Player) {
+        let hand: Vec<String> = player.hand.iter().enumerate().map(|(i, c)| format!("{}:{}", i, c)).collect();
+        println!("Player {} hand: {}", player.username, hand.join(" "));
+    }
+}
+
+impl PlayerController for StdinController {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        println!("It's {} turn", seen.names[seen.seat]);
+        println!("{}", seen.hand_line());
+        let playable: Vec<String> = seen.legal_moves.iter().map(Card::to_string).collect();
+        println!("Playable cards: {} (type a card, its number or help)", playable.join(" "));
+        Self::read_until_ok(seen, |command| {
+            let card = command.card_in(&seen.hand)?;
+            if seen.legal_moves.contains(&card) {
+                Ok(card)
+            } else {
+                Err(GameError::IllegalCard(card))
+            }
+        })
+    }
+
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError> {
+        println!("{}, choose trumps cards", seen.names[seen.seat]);
+        println!("{}", seen.hand_line());
+        Self::read_until_ok(seen, Command::trump)
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        println!("{}", seen.hand_line());
+
+        let first: Card = Self::read_until_ok(seen, |command| command.card_in(&seen.hand))?;
+        let second: Card = Self::read_until_ok(seen, |command| command.card_in(&seen.hand))?;
+        Ok([first, second])
+    }
+
+    fn rejected(&mut self, seen: &Observation, error: GameError) {
+        println!("{}, try again", error);
+        println!("{}", seen.hand_line());
+    }
+
+    fn inform(&mut self, text: &str) {
+        println!("{}", text);
+    }
+}
+
+/*
+    Several humans sharing one console. Before every turn the screen is
+    cleared and the seat is asked to take the device, then it sees only its
+    own observation of the game, never the hands of the others.
+*/
+#[derive(Default)]
+pub struct HotSeatController {
+    console: StdinController,
+}
+
+impl HotSeatController {
+    pub fn new() -> Self {
+        HotSeatController { console: StdinController::new() }
+    }
+
+    pub fn clear_screen() {
+        print!("\x1b[2J\x1b[H");
+        let _ = io::stdout().flush();
+    }
+
+    /* Wait until the seat has the device, then show what it may see */
+    fn hand_over(seen: &Observation) -> Result<(), GameError> {
+        Self::clear_screen();
+        println!("Pass the device to {} and press Enter", seen.names[seen.seat]);
+        StdinController::read_line()?;
+        Self::clear_screen();
+        print!("{}", seen);
+        Ok(())
+    }
+}
+
+impl PlayerController for HotSeatController {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        Self::hand_over(seen)?;
+        let card = self.console.choose_card(seen)?;
+        Self::clear_screen();
+        Ok(card)
+    }
+
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError> {
+        Self::hand_over(seen)?;
+        let trump = self.console.choose_trump(seen)?;
+        Self::clear_screen();
+        Ok(trump)
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        Self::hand_over(seen)?;
+        let cards = self.console.choose_cards_to_hide(seen)?;
+        Self::clear_screen();
+        Ok(cards)
+    }
+
+    fn rejected(&mut self, seen: &Observation, error: GameError) {
+        self.console.rejected(seen, error);
+    }
+
+    fn inform(&mut self, text: &str) {
+        self.console.inform(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::card::fill_cards;
+    use crate::game::engine::RuleError;
+
+    /* Hides the given pairs in order, quits once they run out, and remembers what it was told */
+    #[derive(Default)]
+    struct Scripted {
+        hides: Vec<[Card; 2]>,
+        errors: Vec<GameError>,
+        events: Vec<Event>,
+    }
+
+    impl PlayerController for Scripted {
+        fn choose_card(&mut self, _seen: &Observation) -> Result<Card, GameError> {
+            unreachable!("Only hiding is scripted")
+        }
+
+        fn choose_trump(&mut self, _seen: &Observation) -> Result<Suit, GameError> {
+            unreachable!("Only hiding is scripted")
+        }
+
+        fn choose_cards_to_hide(&mut self, _seen: &Observation) -> Result<[Card; 2], GameError> {
+            if self.hides.is_empty() {
+                return Err(GameError::Quit);
+            }
+            Ok(self.hides.remove(0))
+        }
+
+        fn rejected(&mut self, _seen: &Observation, error: GameError) {
+            self.errors.push(error);
+        }
+
+        fn notify(&mut self, _seen: &Observation, event: &Event) {
+            self.events.push(event.clone());
+        }
+    }
+
+    /* First deal started, seat 0 declares King and holds the King Heart first */
+    fn dealt_with_king() -> (GameEngine, Vec<Card>) {
+        let mut engine = GameEngine::new();
+        for name in ["A", "B", "C"] {
+            engine.add_player(String::from(name), false).unwrap();
+        }
+        let king: Card = "KH".parse().unwrap();
+        let mut deck = fill_cards();
+        deck.retain(|&c| c != king);
+        deck.insert(0, king);
+        engine.set_next_hands([deck[..12].to_vec(), deck[12..22].to_vec(), deck[22..].to_vec()]);
+        engine.apply(Action::StartDeal).unwrap();
+        (engine, deck)
+    }
+
+    #[test]
+    fn rejected_answers_are_asked_again() {
+        let (mut engine, deck) = dealt_with_king();
+        let (king, first, second) = (deck[0], deck[1], deck[2]);
+        let mut controllers: Vec<Scripted> = (0..3).map(|_| Scripted::default()).collect();
+        controllers[0].hides = vec![[king, first], [first, first], [first, second]];
+        let events = ask_until_accepted(&mut engine, &mut controllers, Decision::HideCards { seat: 0 }).unwrap();
+
+        let hidden = Event::CardsHidden { seat: 0, cards: Some([first, second]) };
+        assert_eq!(events, vec![hidden.clone()]);
+        assert_eq!(controllers[0].errors, vec![GameError::Rule(RuleError::ForbiddenDiscard), GameError::Rule(RuleError::DuplicateCard)]);
+        assert!(controllers[0].hides.is_empty());
+        assert!(controllers[1].errors.is_empty());
+
+        /* Only the declarer is told which cards went away */
+        assert_eq!(controllers[0].events, vec![hidden]);
+        for other in &controllers[1..] {
+            assert_eq!(other.events, vec![Event::CardsHidden { seat: 0, cards: None }]);
+        }
+    }
+
+    #[test]
+    fn a_player_who_quits_stops_the_asking() {
+        let (mut engine, deck) = dealt_with_king();
+        let mut controllers: Vec<Scripted> = (0..3).map(|_| Scripted::default()).collect();
+        controllers[0].hides = vec![[deck[0], deck[1]]];
+
+        let decision = Decision::HideCards { seat: 0 };
+        assert_eq!(ask_until_accepted(&mut engine, &mut controllers, decision), Err(GameError::Quit));
+        assert_eq!(controllers[0].errors, vec![GameError::Rule(RuleError::ForbiddenDiscard)]);
+        assert_eq!(engine.pending_decision(), Some(decision));
+        assert!(controllers.iter().all(|c| c.events.is_empty()));
+    }
+
+    #[test]
+    fn a_full_room_refuses_another_player() {
+        let mut engine = GameEngine::new();
+        for name in ["A", "B", "C"] {
+            engine.add_player(String::from(name), false).unwrap();
+        }
+        assert_eq!(engine.add_player(String::from("D"), false), Err(GameError::RoomFull));
+        assert_eq!(engine.players.len(), 3);
+    }
+}
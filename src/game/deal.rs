@@ -0,0 +1,103 @@
+/*
+    Reproducible deals. Every deal is shuffled from a 64-bit seed, and the
+    seed written in base 36 is the deal ID players can share: starting a deal
+    from the same ID gives the same cards again.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::game::card::{self, serde_as_notation, Card};
+
+const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DealId(pub u64);
+
+impl DealId {
+    /* Fresh ID from the thread random generator */
+    pub fn random() -> Self {
+        DealId(rand::random())
+    }
+
+    /* ID of deal number `deal` of a match played from `seed` (SplitMix64) */
+    pub fn from_match_seed(seed: u64, deal: usize) -> Self {
+        let mut z = seed.wrapping_add((deal as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        DealId(z ^ (z >> 31))
+    }
+
+    /* Hands of the deal, the declarer gets the two extra cards */
+    pub fn deal(&self, deck: &[Card], declarer: usize) -> [Vec<Card>; 3] {
+        let mut rng = StdRng::seed_from_u64(self.0);
+        card::shuffle(deck, declarer, &mut rng)
+    }
+}
+
+impl fmt::Display for DealId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut value = self.0;
+        let mut digits = vec![];
+        loop {
+            digits.push(DIGITS[(value % 36) as usize]);
+            value /= 36;
+            if value == 0 {
+                break;
+            }
+        }
+        digits.reverse();
+        write!(f, "{}", String::from_utf8(digits).unwrap())
+    }
+}
+
+impl FromStr for DealId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if text.is_empty() {
+            return Err(String::from("Deal ID is empty"));
+        }
+        u64::from_str_radix(text, 36)
+            .map(DealId)
+            .map_err(|_| format!("'{}' is not a deal ID", s))
+    }
+}
+
+serde_as_notation!(DealId);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deal_ids_round_trip_through_text() {
+        for value in [0, 35, 36, 123_456_789, u64::MAX] {
+            let id = DealId(value);
+            assert_eq!(id.to_string().parse::<DealId>(), Ok(id));
+        }
+        assert_eq!("zz".parse::<DealId>(), Ok(DealId(36 * 36 - 1)));
+        assert!("".parse::<DealId>().is_err());
+        assert!("K-1".parse::<DealId>().is_err());
+    }
+
+    #[test]
+    fn same_id_deals_the_same_cards() {
+        let deck = card::fill_cards();
+        let id = DealId::from_match_seed(7, 0);
+        assert_eq!(id.deal(&deck, 0), id.deal(&deck, 0));
+        assert_ne!(id.deal(&deck, 0), DealId::from_match_seed(7, 1).deal(&deck, 0));
+
+        /* Moving the declarer moves the hands with it */
+        let first = id.deal(&deck, 0);
+        let second = id.deal(&deck, 2);
+        for seat in 0..3 {
+            assert_eq!(first[seat], second[(seat + 2) % 3]);
+        }
+        assert_eq!(second[2].len(), 12);
+    }
+}
@@ -0,0 +1,1008 @@
+/*
+    Pure state machine of the King rules.
+
+    The engine never reads from stdin and never prints. Front ends ask it what
+    has to happen next with `pending_decision`, feed the answer back through
+    `apply`, and render the returned events however they like.
+*/
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::board::{Board, Trick};
+use crate::game::card::{self, Card, Rank, Suit};
+use crate::game::contract::Contract;
+use crate::game::deal::DealId;
+use crate::game::observation::Observation;
+use crate::game::player::Player;
+use crate::game::schedule::Schedule;
+
+/* Something the engine is waiting for */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Decision {
+    StartDeal,
+    HideCards { seat: usize },
+    ChooseTrump { seat: usize },
+    PlayCard { seat: usize },
+}
+
+/* Input accepted by `GameEngine::apply` */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    StartDeal,
+    HideCards { seat: usize, cards: [Card; 2] },
+    ChooseTrump { seat: usize, trump: Suit },
+    PlayCard { seat: usize, card: Card },
+}
+
+/* Everything that happened as a result of an action, in order */
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    DealStarted { contract: Contract, declarer: usize },
+    CardsDealt { hands: [Vec<Card>; 3] }, // by seat, see `observation::redact` for what a seat gets
+    CardsHidden { seat: usize, cards: Option<[Card; 2]> }, // None in the copy the other seats get
+    TrumpChosen { seat: usize, trump: Suit },
+    CardPlayed { seat: usize, card: Card },
+    TrickWon { seat: usize, card: Card, cards: Vec<Card> },
+    ScoreChanged { seat: usize, delta: i32, total: i32 },
+    DealEnded,
+    MatchOver { standings: Vec<Standing> },
+}
+
+/* Final result of one seat, `place` starts at 1 and is shared on a full tie */
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Standing {
+    pub place: usize,
+    pub seat: usize,
+    pub score: i32,
+    pub by_contract: [i32; 7], // in `Contract::ALL` order
+}
+
+/* Rules of the game an action can break, reported as `GameError::Rule` */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RuleError {
+    NotEnoughPlayers,
+    NotYourTurn,
+    CardNotInHand,
+    DuplicateCard,
+    ForbiddenDiscard,
+    MustFollowSuit,
+    MustTrump,
+    KingOfHeartsLead,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            RuleError::NotEnoughPlayers => "Not enough players",
+            RuleError::NotYourTurn => "It is not your turn",
+            RuleError::CardNotInHand => "Card is not in the hand",
+            RuleError::DuplicateCard => "The same card was chosen twice",
+            RuleError::ForbiddenDiscard => "Cards this game punishes can only be hidden when nothing else is left",
+            RuleError::MustFollowSuit => "You must follow the suit of the first card",
+            RuleError::MustTrump => "You must play a trump when you can not follow suit",
+            RuleError::KingOfHeartsLead => "King Heart can not be laid down first during its game",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/*
+    Anything a player or a front end can get wrong, shown to the player who is
+    then asked again. A rejected action never changes the state. `Quit` and
+    `Save` are no mistakes: a controller returns them when its player leaves
+    the match or wants it saved to a file, which only the program can do
+*/
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GameError {
+    InvalidIndex { input: String, len: usize }, // not a position in a hand of `len` cards
+    IllegalCard(Card),
+    WrongPhase,
+    UnknownTrump(String),
+    ExpectedCard(String),
+    UnknownCommand(String),
+    RoomFull,
+    Rule(RuleError),
+    Quit,
+    Save(String),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::InvalidIndex { input, len } => {
+                write!(f, "'{}' is not a card number, choose one from 0 to {}", input, len.saturating_sub(1))
+            },
+            GameError::IllegalCard(card) => write!(f, "{} can not be played now", card),
+            GameError::WrongPhase => write!(f, "This action is not expected now"),
+            GameError::UnknownTrump(input) => {
+                write!(f, "'{}' is not a suit, type one of C, D, H or S, a suit symbol or its name", input)
+            },
+            GameError::ExpectedCard(input) => {
+                write!(f, "'{}' is not a card, type one like KH, 10s or q♠, or its number in the hand", input)
+            },
+            GameError::UnknownCommand(input) if input.is_empty() => write!(f, "Type a card, its number or help"),
+            GameError::UnknownCommand(input) => {
+                write!(f, "'{}' is neither a card nor a command, type help to see the commands", input)
+            },
+            GameError::RoomFull => write!(f, "Room has already been filled"),
+            GameError::Rule(error) => write!(f, "{}", error),
+            GameError::Quit => write!(f, "The player left the match"),
+            GameError::Save(path) => write!(f, "The player wants the match saved to {}", path),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<RuleError> for GameError {
+    fn from(error: RuleError) -> Self {
+        GameError::Rule(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+enum Phase {
+    BetweenDeals,
+    Hiding,
+    ChoosingTrump,
+    Playing,
+    MatchOver,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct GameEngine {
+    pub players: Vec<Player>,
+    pub deck: Vec<Card>,
+    pub board: Board,  // size 3
+    #[serde(default)]
+    pub discards: Vec<(usize, [Card; 2])>, // the declarer and the two cards they hid, one per deal in order
+    pub schedule: Schedule,
+    phase: Phase,
+    turn: usize,
+    trump: Option<Suit>,
+    #[serde(default)]
+    seed: Option<u64>, // deals follow from it when set
+    #[serde(default)]
+    next_deal: Option<DealId>,
+    #[serde(default)]
+    deal_id: Option<DealId>,
+    #[serde(default)]
+    next_hands: Option<[Vec<Card>; 3]>,
+}
+
+impl Default for GameEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameEngine {
+    pub fn new() -> Self {
+        GameEngine {
+            players: vec![],
+            discards: vec![],
+            deck: card::fill_cards(),
+            board: Board::new(),
+            schedule: Schedule::new(),
+            phase: Phase::BetweenDeals,
+            turn: 0,
+            trump: None,
+            seed: None,
+            next_deal: None,
+            deal_id: None,
+            next_hands: None,
+        }
+    }
+
+    /* Every deal of the match is derived from this seed */
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /* The next deal is dealt from this ID instead of a fresh one */
+    pub fn set_next_deal(&mut self, id: DealId) {
+        self.next_deal = Some(id);
+    }
+
+    /*
+        The next deal uses these hands instead of shuffling, the first one
+        goes to the declarer and the others follow around the table
+    */
+    pub fn set_next_hands(&mut self, hands: [Vec<Card>; 3]) {
+        self.next_hands = Some(hands);
+    }
+
+    /* ID of the current deal, None before the first one or for preset hands */
+    pub fn deal_id(&self) -> Option<DealId> {
+        self.deal_id
+    }
+
+    /* Handle adding new player */
+    pub fn add_player(&mut self, username: String, is_his_play: bool) -> Result<(), GameError> {
+        if self.room_is_filled() {
+            return Err(GameError::RoomFull);
+        }
+        self.players.push(Player::new(username, is_his_play));
+        Ok(())
+    }
+
+    pub fn room_is_filled(&self) -> bool {
+        self.players.len() == 3
+    }
+
+    /* Current game type from the schedule, None once the match is over */
+    pub fn contract(&self) -> Option<Contract> {
+        self.schedule.get_game()
+    }
+
+    /* Contract of the deal being played, every phase but the end of the match has one */
+    fn deal_contract(&self) -> Contract {
+        self.contract().expect("No deal is played after the match")
+    }
+
+    /* None while no trump has been announced */
+    pub fn trump(&self) -> Option<Suit> {
+        self.trump
+    }
+
+    /* Cards laid down so far in the current deal */
+    pub fn gone_cards(&self) -> Vec<Card> {
+        self.board.played_cards()
+    }
+
+    /* Seat of the player whose play it is, taken from the schedule */
+    pub fn declarer(&self) -> usize {
+        self.schedule.declarer()
+    }
+
+    /* What the engine needs next, None while the room is not filled */
+    pub fn pending_decision(&self) -> Option<Decision> {
+        if !self.room_is_filled() {
+            return None;
+        }
+        let decision = match self.phase {
+            Phase::BetweenDeals => Decision::StartDeal,
+            Phase::Hiding => Decision::HideCards { seat: self.declarer() },
+            Phase::ChoosingTrump => Decision::ChooseTrump { seat: self.declarer() },
+            Phase::Playing => Decision::PlayCard { seat: self.turn },
+            Phase::MatchOver => return None,
+        };
+        Some(decision)
+    }
+
+    pub fn apply(&mut self, action: Action) -> Result<Vec<Event>, GameError> {
+        if !self.room_is_filled() {
+            return Err(RuleError::NotEnoughPlayers.into());
+        }
+        match action {
+            Action::StartDeal => self.start_deal(),
+            Action::HideCards { seat, cards } => self.hide_cards(seat, cards),
+            Action::ChooseTrump { seat, trump } => self.choose_trump(seat, trump),
+            Action::PlayCard { seat, card } => self.play_card(seat, card),
+        }
+    }
+
+    fn start_deal(&mut self) -> Result<Vec<Event>, GameError> {
+        if self.phase != Phase::BetweenDeals {
+            return Err(GameError::WrongPhase);
+        }
+
+        /* Shuffle cards and deal them to players */
+        let declarer = self.declarer();
+        let shuffled: [Vec<Card>; 3] = if let Some(mut hands) = self.next_hands.take() {
+            hands.rotate_right(declarer);
+            self.deal_id = None;
+            hands
+        } else {
+            let id = match (self.next_deal.take(), self.seed) {
+                (Some(id), _) => id,
+                (None, Some(seed)) => DealId::from_match_seed(seed, self.schedule.deals_played()),
+                (None, None) => DealId::random(),
+            };
+            self.deal_id = Some(id);
+            id.deal(&self.deck, declarer)
+        };
+        for (seat, (player, hand)) in self.players.iter_mut().zip(shuffled).enumerate() {
+            player.hand = hand;
+            player.is_his_play = seat == declarer;
+        }
+
+        /* The declarer leads the first trick */
+        self.board.start(declarer);
+        self.trump = None;
+        self.turn = declarer;
+        self.phase = Phase::Hiding;
+
+        Ok(vec![
+            Event::DealStarted { contract: self.deal_contract(), declarer: self.declarer() },
+            Event::CardsDealt { hands: [0, 1, 2].map(|seat| self.players[seat].hand.clone()) },
+        ])
+    }
+
+    fn hide_cards(&mut self, seat: usize, cards: [Card; 2]) -> Result<Vec<Event>, GameError> {
+        if self.phase != Phase::Hiding {
+            return Err(GameError::WrongPhase);
+        }
+        if seat != self.declarer() {
+            return Err(RuleError::NotYourTurn.into());
+        }
+        if cards[0] == cards[1] {
+            return Err(RuleError::DuplicateCard.into());
+        }
+        /* Everything is checked before the hand is touched */
+        if cards.iter().any(|c| !self.players[seat].has_card(c)) {
+            return Err(RuleError::CardNotInHand.into());
+        }
+        if !self.is_legal_discard(seat, cards) {
+            return Err(RuleError::ForbiddenDiscard.into());
+        }
+
+        for c in cards {
+            self.players[seat].draw_card(c);
+        }
+        self.discards.push((seat, cards));
+
+        self.phase = if self.deal_contract().has_trump() {
+            Phase::ChoosingTrump
+        } else {
+            Phase::Playing
+        };
+
+        Ok(vec![Event::CardsHidden { seat, cards: Some(cards) }])
+    }
+
+    fn choose_trump(&mut self, seat: usize, trump: Suit) -> Result<Vec<Event>, GameError> {
+        if self.phase != Phase::ChoosingTrump {
+            return Err(GameError::WrongPhase);
+        }
+        if seat != self.declarer() {
+            return Err(RuleError::NotYourTurn.into());
+        }
+        self.trump = Some(trump);
+        self.phase = Phase::Playing;
+        Ok(vec![Event::TrumpChosen { seat, trump }])
+    }
+
+    fn play_card(&mut self, seat: usize, card: Card) -> Result<Vec<Event>, GameError> {
+        if self.phase != Phase::Playing {
+            return Err(GameError::WrongPhase);
+        }
+        if seat != self.turn {
+            return Err(RuleError::NotYourTurn.into());
+        }
+        if !self.players[seat].has_card(&card) {
+            return Err(RuleError::CardNotInHand.into());
+        }
+        self.check_play(seat, &card)?;
+
+        /* Remove from players' hand */
+        self.players[seat].draw_card(card);
+
+        self.board.play(seat, card);
+
+        let mut events = vec![Event::CardPlayed { seat, card }];
+
+        if self.board.trick.len() < 3 {
+            self.turn = (self.turn + 1) % 3;
+            return Ok(events);
+        }
+
+        /* Case when there are 3 cards on the board */
+        let taken_cards: Vec<Card> = self.board.trick.cards();
+        let (winner, biggest_card) = self.board.trick.winner(self.trump).expect("A full trick has a winner");
+
+        events.push(Event::TrickWon { seat: winner, card: biggest_card, cards: taken_cards.clone() });
+
+        /*  Get final score of current type of game
+            If it is NOT -1, then that means special cards occured on the board
+        */
+        let is_last_two = self.board.is_last_two();
+        let final_score: i32 = self.board.handle_current_game(self.deal_contract(), &taken_cards, is_last_two);
+
+        let mut deal_is_over = false;
+        if final_score != -1 {
+            self.players[winner].score += final_score;
+            let kind = self.deal_contract().index();
+            self.players[winner].by_contract[kind] += final_score;
+            events.push(Event::ScoreChanged {
+                seat: winner,
+                delta: final_score,
+                total: self.players[winner].score,
+            });
+            deal_is_over = self.special_cards_are_gone();
+        }
+
+        self.board.finish_trick(winner);
+        self.turn = winner;
+
+        if deal_is_over || self.players[winner].hand.is_empty() {
+            events.push(Event::DealEnded);
+            self.schedule.next_game();
+            self.phase = Phase::BetweenDeals;
+            if self.schedule.is_over() {
+                self.phase = Phase::MatchOver;
+                events.push(Event::MatchOver { standings: self.standings() });
+            }
+        }
+
+        Ok(events)
+    }
+
+    /* Seat that put away two cards in the current or just finished deal, and those cards */
+    pub fn talon(&self) -> Option<(usize, [Card; 2])> {
+        if self.phase == Phase::Hiding {
+            return None;
+        }
+        self.discards.last().copied()
+    }
+
+    /* Unfinished trick with the seat of every card, in play order */
+    pub fn current_trick(&self) -> Vec<(usize, Card)> {
+        self.board.trick.plays.clone()
+    }
+
+    /* What `seat` may know right now, see `Observation` */
+    pub fn observation(&self, seat: usize) -> Observation {
+        Observation::of(self, seat)
+    }
+
+    /*
+        Engine in the position `seen` shows, with `hands` as the cards of
+        every seat. Bots that search play deals out on it from what they guess
+    */
+    pub fn from_observation(seen: &Observation, hands: [Vec<Card>; 3]) -> Self {
+        let mut engine = GameEngine::new();
+        for (seat, hand) in hands.into_iter().enumerate() {
+            let mut player = Player::new(seen.names[seat].clone(), seat == seen.declarer);
+            player.hand = hand;
+            player.score = seen.scores[seat];
+            engine.players.push(player);
+        }
+        for _ in 0..seen.deals_played {
+            engine.schedule.next_game();
+        }
+
+        engine.board.history = seen.tricks
+            .iter()
+            .map(|trick| Trick {
+                leader: trick.leader,
+                plays: trick.cards.iter().enumerate().map(|(i, card)| ((trick.leader + i) % 3, *card)).collect(),
+            })
+            .collect();
+        let leader = seen.tricks.last().map_or(seen.declarer, |trick| trick.winner);
+        engine.board.trick = Trick { leader, plays: seen.trick.clone() };
+        if let Some(cards) = seen.hidden {
+            engine.discards.push((seen.declarer, cards));
+        }
+
+        engine.trump = seen.trump;
+        engine.turn = seen.declarer;
+        engine.phase = match seen.decision {
+            Some(Decision::StartDeal) => Phase::BetweenDeals,
+            Some(Decision::HideCards { .. }) => Phase::Hiding,
+            Some(Decision::ChooseTrump { .. }) => Phase::ChoosingTrump,
+            Some(Decision::PlayCard { seat }) => {
+                engine.turn = seat;
+                Phase::Playing
+            },
+            None => Phase::MatchOver,
+        };
+        engine
+    }
+
+    /* True once all 27 deals have been played */
+    pub fn is_match_over(&self) -> bool {
+        self.phase == Phase::MatchOver
+    }
+
+    /*
+        Seats from best to worst. Equal totals are split by the points won in
+        the '+' games, seats still equal after that share the place.
+    */
+    pub fn standings(&self) -> Vec<Standing> {
+        let plus = Contract::Plus.index();
+        let key = |s: &Standing| (s.score, s.by_contract[plus]);
+
+        let mut standings: Vec<Standing> = self.players
+            .iter()
+            .enumerate()
+            .map(|(seat, p)| Standing { place: 0, seat, score: p.score, by_contract: p.by_contract })
+            .collect();
+        standings.sort_by(|a, b| key(b).cmp(&key(a)).then(a.seat.cmp(&b.seat)));
+
+        for i in 0..standings.len() {
+            standings[i].place = if i > 0 && key(&standings[i]) == key(&standings[i - 1]) {
+                standings[i - 1].place
+            } else {
+                i + 1
+            };
+        }
+        standings
+    }
+
+    /* Start the schedule over with the same seats and zero scores */
+    pub fn rematch(&mut self) {
+        for player in self.players.iter_mut() {
+            player.hand.clear();
+            player.score = 0;
+            player.by_contract = [0; 7];
+        }
+        self.schedule = Schedule::new();
+        self.board = Board::new();
+        self.discards.clear();
+        self.trump = None;
+        self.turn = 0;
+        self.phase = Phase::BetweenDeals;
+    }
+
+    /* Exact set of cards the seat may lay down now, empty when it is not its turn */
+    pub fn legal_moves(&self, seat: usize) -> Vec<Card> {
+        if self.phase != Phase::Playing || seat != self.turn {
+            return vec![];
+        }
+        self.players[seat].hand
+            .iter()
+            .filter(|c| self.check_play(seat, c).is_ok())
+            .copied()
+            .collect()
+    }
+
+    /* Validate follow-suit, trump and King Heart rules for a card about to be played */
+    fn check_play(&self, seat: usize, card: &Card) -> Result<(), RuleError> {
+        let hand = &self.players[seat].hand;
+
+        /*
+            If it is kings game and player draw King Heart on the firstly,
+            that is illegal move and we need to prevent it.
+        */
+        let current_suit = match self.board.trick.lead_suit() {
+            Some(suit) => suit,
+            None => {
+                if self.contract() == Some(Contract::King) &&
+                   card.suit == Suit::Hearts &&
+                   card.rank == Rank::King &&
+                   hand.iter().any(|c| c.suit != Suit::Hearts)
+                {
+                    return Err(RuleError::KingOfHeartsLead);
+                }
+                return Ok(());
+            },
+        };
+        if card.suit == current_suit {
+            return Ok(());
+        }
+
+        /*
+            If first suit of the first card on the board is not the same as players drawn card
+            and player has the card of the suit, then it is an illegal move
+        */
+        if Self::contains_specific_suit(hand, current_suit) {
+            return Err(RuleError::MustFollowSuit);
+        }
+
+        /* Player who can not follow suit must use trump if he holds one */
+        if let Some(trump) = self.trump {
+            if card.suit != trump && Self::contains_specific_suit(hand, trump) {
+                return Err(RuleError::MustTrump);
+            }
+        }
+
+        Ok(())
+    }
+
+    /* Cards that cost points in the current game, hidden only when nothing else is left */
+    pub fn is_forbidden_discard(&self, card: &Card) -> bool {
+        self.contract().is_some_and(|c| c.is_penalty_card(card))
+    }
+
+    /*
+        Cards the seat may hide. Penalty cards only fill the slots the other
+        cards leave, so when there are fewer than two others those come first
+        and must all be hidden. The first two cards are always a legal pair
+    */
+    pub fn legal_discards(&self, seat: usize) -> Vec<Card> {
+        let (mut allowed, penalty): (Vec<Card>, Vec<Card>) =
+            self.players[seat].hand.iter().copied().partition(|c| !self.is_forbidden_discard(c));
+        if allowed.len() < 2 {
+            allowed.extend(penalty);
+        }
+        allowed
+    }
+
+    /* Whether the seat may hide these two cards of its hand together */
+    pub fn is_legal_discard(&self, seat: usize, cards: [Card; 2]) -> bool {
+        if self.phase != Phase::Hiding || seat != self.declarer() {
+            return false;
+        }
+        let safe = |c: &&Card| !self.is_forbidden_discard(c);
+        let in_hand = self.players[seat].hand.iter().filter(safe).count();
+        cards.iter().filter(safe).count() == in_hand.min(2)
+    }
+
+    /* Every pair of cards the seat may hide, in the order of `legal_discards` */
+    pub fn legal_discard_pairs(&self, seat: usize) -> Vec<[Card; 2]> {
+        let allowed = self.legal_discards(seat);
+        let mut pairs = vec![];
+        for (i, first) in allowed.iter().enumerate() {
+            for second in &allowed[i + 1..] {
+                if self.is_legal_discard(seat, [*first, *second]) {
+                    pairs.push([*first, *second]);
+                }
+            }
+        }
+        pairs
+    }
+
+    /* Kings game ends with the King Heart, queens and jacks games once all four are gone */
+    fn special_cards_are_gone(&self) -> bool {
+        match self.deal_contract() {
+            Contract::King => true,
+            Contract::Queens => Self::count_specific_card(&self.gone_cards(), Rank::Queen) == 4,
+            Contract::Jacks => Self::count_specific_card(&self.gone_cards(), Rank::Jack) == 4,
+            _ => false,
+        }
+    }
+
+    pub fn contains_specific_suit(hand: &[Card], suit: Suit) -> bool {
+        hand.iter().any(|c| c.suit == suit)
+    }
+
+    pub fn count_specific_card(gone_cards: &[Card], rank: Rank) -> u8 {
+        gone_cards.iter().filter(|c| c.rank == rank).count() as u8
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(notation: &str) -> Card {
+        notation.parse().unwrap()
+    }
+
+    /* Engine in the middle of a deal of the given schedule position */
+    fn engine_playing(deals_played: usize, hands: [Vec<Card>; 3]) -> GameEngine {
+        let mut engine = GameEngine::new();
+        engine.add_player(String::from("A"), true).unwrap();
+        engine.add_player(String::from("B"), false).unwrap();
+        engine.add_player(String::from("C"), false).unwrap();
+        for _ in 0..deals_played {
+            engine.schedule.next_game();
+        }
+        for (player, hand) in engine.players.iter_mut().zip(hands) {
+            player.hand = hand;
+        }
+        engine.phase = Phase::Playing;
+        engine
+    }
+
+    #[test]
+    fn a_scripted_deal_runs_from_start_to_end() {
+        let mut engine = GameEngine::new();
+        for name in ["A", "B", "C"] {
+            engine.add_player(String::from(name), false).unwrap();
+        }
+        let special = [card("7H"), card("KH"), card("8H")];
+        let rest: Vec<Card> = card::fill_cards().into_iter().filter(|c| !special.contains(c)).collect();
+        let mut first = vec![special[0]];
+        first.extend_from_slice(&rest[..11]);
+        let mut second = vec![special[1]];
+        second.extend_from_slice(&rest[11..20]);
+        let mut third = vec![special[2]];
+        third.extend_from_slice(&rest[20..]);
+        engine.set_next_hands([first.clone(), second, third]);
+
+        assert_eq!(engine.pending_decision(), Some(Decision::StartDeal));
+        engine.apply(Action::StartDeal).unwrap();
+        assert_eq!(engine.pending_decision(), Some(Decision::HideCards { seat: 0 }));
+        let hidden = [first[10], first[11]];
+        assert_eq!(
+            engine.apply(Action::HideCards { seat: 0, cards: hidden }),
+            Ok(vec![Event::CardsHidden { seat: 0, cards: Some(hidden) }])
+        );
+        assert_eq!(engine.players[0].hand.len(), 10);
+
+        /* In King the deal is over as soon as the King Heart is taken */
+        for (seat, card) in [(0, special[0]), (1, special[1])] {
+            assert_eq!(engine.pending_decision(), Some(Decision::PlayCard { seat }));
+            assert_eq!(engine.apply(Action::PlayCard { seat, card }), Ok(vec![Event::CardPlayed { seat, card }]));
+        }
+        assert_eq!(
+            engine.apply(Action::PlayCard { seat: 2, card: special[2] }),
+            Ok(vec![
+                Event::CardPlayed { seat: 2, card: special[2] },
+                Event::TrickWon { seat: 1, card: special[1], cards: special.to_vec() },
+                Event::ScoreChanged { seat: 1, delta: -40, total: -40 },
+                Event::DealEnded,
+            ])
+        );
+        assert_eq!(engine.players.iter().map(|p| p.score).collect::<Vec<_>>(), vec![0, -40, 0]);
+        assert_eq!(engine.pending_decision(), Some(Decision::StartDeal));
+        assert_eq!(engine.declarer(), 1);
+    }
+
+    #[test]
+    fn leader_may_not_open_with_king_heart_in_kings_game() {
+        let engine = engine_playing(0, [
+            vec![card("KH"), card("7S")],
+            vec![card("7H"), card("8S")],
+            vec![card("8H"), card("9S")],
+        ]);
+        assert_eq!(engine.legal_moves(0), vec![card("7S")]);
+    }
+
+    #[test]
+    fn leader_holding_only_hearts_may_open_with_king_heart() {
+        let engine = engine_playing(0, [
+            vec![card("KH"), card("7H")],
+            vec![card("7S"), card("8S")],
+            vec![card("8D"), card("9D")],
+        ]);
+        assert_eq!(engine.legal_moves(0), vec![card("KH"), card("7H")]);
+    }
+
+    #[test]
+    fn follower_must_follow_suit() {
+        let mut engine = engine_playing(3, [
+            vec![card("AS"), card("7H")],
+            vec![card("7S"), card("QD"), card("QS")],
+            vec![card("8D"), card("9C")],
+        ]);
+        engine.apply(Action::PlayCard { seat: 0, card: card("AS") }).unwrap();
+        assert_eq!(engine.legal_moves(1), vec![card("7S"), card("QS")]);
+        assert_eq!(
+            engine.apply(Action::PlayCard { seat: 1, card: card("QD") }),
+            Err(GameError::Rule(RuleError::MustFollowSuit))
+        );
+    }
+
+    #[test]
+    fn void_follower_must_trump_in_plus_game() {
+        let mut engine = engine_playing(6, [
+            vec![card("AS"), card("7H")],
+            vec![card("7D"), card("QC"), card("8C")],
+            vec![card("8D"), card("9H")],
+        ]);
+        engine.trump = Some(Suit::Clubs);
+        engine.apply(Action::PlayCard { seat: 0, card: card("AS") }).unwrap();
+        assert_eq!(engine.legal_moves(1), vec![card("QC"), card("8C")]);
+        assert_eq!(
+            engine.apply(Action::PlayCard { seat: 1, card: card("7D") }),
+            Err(GameError::Rule(RuleError::MustTrump))
+        );
+    }
+
+    #[test]
+    fn void_follower_without_trump_may_play_anything() {
+        let mut engine = engine_playing(3, [
+            vec![card("AS"), card("7H")],
+            vec![card("7D"), card("QC")],
+            vec![card("8D"), card("9H")],
+        ]);
+        engine.apply(Action::PlayCard { seat: 0, card: card("AS") }).unwrap();
+        assert_eq!(engine.legal_moves(1), vec![card("7D"), card("QC")]);
+    }
+
+    #[test]
+    fn only_the_seat_to_move_has_legal_moves() {
+        let engine = engine_playing(3, [
+            vec![card("AS")],
+            vec![card("7D")],
+            vec![card("8D")],
+        ]);
+        assert!(engine.legal_moves(1).is_empty());
+        assert!(engine.legal_moves(2).is_empty());
+    }
+
+    #[test]
+    fn last_trick_of_the_last_deal_ends_the_match() {
+        let mut engine = engine_playing(26, [
+            vec![card("AS")],
+            vec![card("7S")],
+            vec![card("8S")],
+        ]);
+        engine.trump = Some(Suit::Hearts);
+        engine.apply(Action::PlayCard { seat: 0, card: card("AS") }).unwrap();
+        engine.apply(Action::PlayCard { seat: 1, card: card("7S") }).unwrap();
+        let events = engine.apply(Action::PlayCard { seat: 2, card: card("8S") }).unwrap();
+
+        assert!(events.contains(&Event::DealEnded));
+        assert!(matches!(events.last(), Some(Event::MatchOver { .. })));
+        assert!(engine.is_match_over());
+        assert_eq!(engine.pending_decision(), None);
+        assert_eq!(engine.apply(Action::StartDeal), Err(GameError::WrongPhase));
+
+        /* Nothing is left to declare or hide */
+        assert_eq!(engine.contract(), None);
+        assert!(!engine.is_forbidden_discard(&card("KH")));
+        assert!(engine.legal_discards(0).is_empty());
+        assert!(engine.legal_discard_pairs(0).is_empty());
+        assert!(!engine.is_legal_discard(0, [card("AS"), card("7S")]));
+    }
+
+    #[test]
+    fn standings_break_ties_with_plus_points() {
+        let mut engine = engine_playing(0, [vec![], vec![], vec![]]);
+        let plus = Contract::Plus.index();
+        let hearts = Contract::Hearts.index();
+        for (seat, (score, plus_points)) in [(-10, 20), (-10, 30), (-10, 20)].iter().enumerate() {
+            engine.players[seat].score = *score;
+            engine.players[seat].by_contract[plus] = *plus_points;
+            engine.players[seat].by_contract[hearts] = *score - *plus_points;
+        }
+
+        let standings = engine.standings();
+        let order: Vec<(usize, usize)> = standings.iter().map(|s| (s.place, s.seat)).collect();
+        assert_eq!(order, vec![(1, 1), (2, 0), (2, 2)]);
+        assert_eq!(standings[0].by_contract[hearts], -40);
+    }
+
+    #[test]
+    fn rematch_starts_the_schedule_over() {
+        let mut engine = engine_playing(26, [vec![card("AS")], vec![], vec![]]);
+        engine.players[0].score = 12;
+        engine.phase = Phase::MatchOver;
+
+        engine.rematch();
+        assert_eq!(engine.schedule.deals_played(), 0);
+        assert_eq!(engine.pending_decision(), Some(Decision::StartDeal));
+        assert!(engine.players.iter().all(|p| p.score == 0 && p.hand.is_empty()));
+    }
+
+    #[test]
+    fn declarer_and_talon_rotate_over_the_whole_match() {
+        use crate::game::testing::BotTable;
+
+        let mut table = BotTable::new(0);
+        let mut declarers = vec![];
+        let mut leaders = vec![];
+        let mut hidden = vec![];
+        while let Some(events) = table.step() {
+            let engine = &table.engine;
+            if let Some(Event::CardsHidden { seat, cards: Some(cards) }) = events.first() {
+                assert_eq!(engine.talon(), Some((*seat, *cards)));
+                hidden.push((*seat, *cards));
+            }
+            if let Some(Event::DealStarted { declarer, .. }) = events.first() {
+                let sizes: Vec<usize> = engine.players.iter().map(|p| p.hand.len()).collect();
+                let expected: Vec<usize> = (0..3).map(|s| if s == *declarer { 12 } else { 10 }).collect();
+                assert_eq!(sizes, expected);
+                assert!(engine.players[*declarer].is_his_play);
+                declarers.push(*declarer);
+            }
+            if let Some(Event::CardPlayed { seat, .. }) = events.first() {
+                if engine.gone_cards().len() == 1 {
+                    leaders.push(*seat);
+                }
+            }
+        }
+
+        let expected: Vec<usize> = (0..27).map(|deal| deal % 3).collect();
+        assert_eq!(declarers, expected);
+        assert_eq!(leaders, expected);
+        assert_eq!(table.engine.discards, hidden);
+        assert!(table.engine.is_match_over());
+    }
+
+    /* Engine waiting for the declarer of the given schedule position to hide two cards */
+    fn engine_hiding(deals_played: usize, hand: Vec<Card>) -> GameEngine {
+        let mut engine = engine_playing(deals_played, [vec![], vec![], vec![]]);
+        let declarer = engine.declarer();
+        engine.players[declarer].hand = hand;
+        engine.phase = Phase::Hiding;
+        engine
+    }
+
+    #[test]
+    fn illegal_discards_leave_the_hand_alone() {
+        let hand = vec![card("KH"), card("7S"), card("8S"), card("9D")];
+        let mut engine = engine_hiding(0, hand.clone());
+        let seat = engine.declarer();
+
+        for (cards, error) in [
+            ([card("KH"), card("7S")], RuleError::ForbiddenDiscard),
+            ([card("7S"), card("7S")], RuleError::DuplicateCard),
+            ([card("7S"), card("AD")], RuleError::CardNotInHand),
+        ] {
+            assert_eq!(engine.apply(Action::HideCards { seat, cards }), Err(GameError::Rule(error)));
+            assert_eq!(engine.players[seat].hand, hand);
+            assert!(engine.discards.is_empty());
+            assert_eq!(engine.talon(), None);
+            assert_eq!(engine.pending_decision(), Some(Decision::HideCards { seat }));
+        }
+
+        engine.apply(Action::HideCards { seat, cards: [card("7S"), card("9D")] }).unwrap();
+        assert_eq!(engine.players[seat].hand, vec![card("KH"), card("8S")]);
+        assert_eq!(engine.talon(), Some((seat, [card("7S"), card("9D")])));
+    }
+
+    #[test]
+    fn penalty_cards_may_be_hidden_when_nothing_else_is_left() {
+        let mut schedule = Schedule::new();
+        let hearts = (0..crate::game::schedule::DEALS)
+            .find(|_| {
+                let found = schedule.get_game() == Some(Contract::Hearts);
+                schedule.next_game();
+                found
+            })
+            .unwrap();
+
+        /* The one other card has to go, a heart only fills the second place */
+        let hand = vec![card("7H"), card("8H"), card("9H"), card("7S")];
+        let mut engine = engine_hiding(hearts, hand.clone());
+        let seat = engine.declarer();
+        assert_eq!(engine.legal_discards(seat), vec![card("7S"), card("7H"), card("8H"), card("9H")]);
+        assert_eq!(
+            engine.legal_discard_pairs(seat),
+            vec![[card("7S"), card("7H")], [card("7S"), card("8H")], [card("7S"), card("9H")]]
+        );
+        assert_eq!(
+            engine.apply(Action::HideCards { seat, cards: [card("7H"), card("8H")] }),
+            Err(GameError::Rule(RuleError::ForbiddenDiscard))
+        );
+        assert_eq!(engine.players[seat].hand, hand);
+        engine.apply(Action::HideCards { seat, cards: [card("9H"), card("7S")] }).unwrap();
+
+        let engine = engine_hiding(hearts, vec![card("7H"), card("8H"), card("9H")]);
+        assert_eq!(engine.legal_discard_pairs(engine.declarer()).len(), 3);
+
+        let engine = engine_hiding(hearts, vec![card("7H"), card("8H"), card("7S"), card("8S")]);
+        assert_eq!(engine.legal_discards(engine.declarer()), vec![card("7S"), card("8S")]);
+        assert_eq!(engine.legal_discard_pairs(engine.declarer()), vec![[card("7S"), card("8S")]]);
+    }
+
+    #[test]
+    fn seeded_engines_deal_the_same_cards() {
+        let started = |mut engine: GameEngine| -> GameEngine {
+            for name in ["A", "B", "C"] {
+                engine.add_player(String::from(name), false).unwrap();
+            }
+            engine.apply(Action::StartDeal).unwrap();
+            engine
+        };
+        let hands = |engine: &GameEngine| -> Vec<Vec<Card>> {
+            engine.players.iter().map(|p| p.hand.clone()).collect()
+        };
+
+        let first = started(GameEngine::new().with_seed(42));
+        let second = started(GameEngine::new().with_seed(42));
+        assert_eq!(hands(&first), hands(&second));
+        assert_eq!(first.deal_id(), second.deal_id());
+
+        let mut replay = GameEngine::new();
+        replay.set_next_deal(first.deal_id().unwrap());
+        assert_eq!(hands(&started(replay)), hands(&first));
+    }
+
+    #[test]
+    fn an_observation_and_the_hands_rebuild_the_position() {
+        use crate::game::testing::BotTable;
+
+        let mut table = BotTable::new(8);
+        table.play_until(|engine| engine.board.history.len() == 4 && engine.current_trick().len() == 1);
+        let engine = &table.engine;
+        let seat = engine.board.trick.plays[0].0;
+        let seen = engine.observation((seat + 1) % 3);
+
+        let hands = [0, 1, 2].map(|s| engine.players[s].hand.clone());
+        let world = GameEngine::from_observation(&seen, hands);
+        assert_eq!(world.pending_decision(), engine.pending_decision());
+        assert_eq!(world.contract(), engine.contract());
+        for s in 0..3 {
+            assert_eq!(world.observation(s).legal_moves, engine.observation(s).legal_moves);
+            assert_eq!(world.observation(s).tricks, engine.observation(s).tricks);
+        }
+        assert_eq!(seen.hand_sizes, vec![engine.players[0].hand.len(), engine.players[1].hand.len(), engine.players[2].hand.len()]);
+    }
+}
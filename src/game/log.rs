@@ -0,0 +1,260 @@
+/*
+    Event-sourced record of a match. The log keeps the engine as it was when
+    recording started and every event the engine emitted after that. Replaying
+    turns the events back into actions and checks that the engine emits the
+    very same events again, so a log can not claim a score it did not earn.
+
+    On disk it is JSON lines: the starting engine first, then one event per
+    line, which lets the console append to it after every action.
+*/
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::game::engine::{Action, Event, GameEngine, GameError};
+
+#[derive(Clone)]
+pub struct GameLog {
+    pub initial: GameEngine,
+    pub events: Vec<Event>,
+}
+
+/* Why a log could not be replayed, `index` is the offending event */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplayError {
+    pub index: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "event {}: {}", self.index + 1, self.reason)
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl GameLog {
+    /* Start recording from the current state of the engine */
+    pub fn new(engine: &GameEngine) -> Self {
+        GameLog { initial: engine.clone(), events: vec![] }
+    }
+
+    pub fn record(&mut self, events: &[Event]) {
+        self.events.extend_from_slice(events);
+    }
+
+    /* Engine after every recorded event */
+    pub fn replay(&self) -> Result<GameEngine, ReplayError> {
+        self.replay_to(self.events.len())
+    }
+
+    /*
+        Engine after the first `count` events. An action emits several events
+        at once, a count in the middle of them gives the state after all of them.
+    */
+    pub fn replay_to(&self, count: usize) -> Result<GameEngine, ReplayError> {
+        let mut engine = self.initial.clone();
+        let mut expected: VecDeque<Event> = VecDeque::new();
+        let count = count.min(self.events.len());
+
+        for (index, event) in self.events[..count].iter().enumerate() {
+            let error = |reason: String| ReplayError { index, reason };
+
+            if expected.is_empty() {
+                let action = self.action_for(&mut engine, index).map_err(error)?;
+                let emitted = engine
+                    .apply(action)
+                    .map_err(|e: GameError| error(format!("rejected by the rules: {}", e)))?;
+                expected.extend(emitted);
+            }
+            match expected.pop_front() {
+                Some(emitted) if emitted == *event => {},
+                emitted => return Err(error(format!("recorded {:?} but the engine gave {:?}", event, emitted))),
+            }
+        }
+        Ok(engine)
+    }
+
+    /* Action that must have caused the event at `index`, a deal also gets its recorded cards */
+    fn action_for(&self, engine: &mut GameEngine, index: usize) -> Result<Action, String> {
+        match &self.events[index] {
+            Event::DealStarted { declarer, .. } => {
+                let mut hands = match self.events.get(index + 1) {
+                    Some(Event::CardsDealt { hands }) => hands.clone(),
+                    _ => return Err(String::from("deal without its cards")),
+                };
+                /* Preset hands are given from the declarer around the table */
+                hands.rotate_left(*declarer);
+                engine.set_next_hands(hands);
+                Ok(Action::StartDeal)
+            },
+            Event::CardsHidden { seat, cards: Some(cards) } => Ok(Action::HideCards { seat: *seat, cards: *cards }),
+            Event::TrumpChosen { seat, trump } => Ok(Action::ChooseTrump { seat: *seat, trump: *trump }),
+            Event::CardPlayed { seat, card } => Ok(Action::PlayCard { seat: *seat, card: *card }),
+            other => Err(format!("{:?} does not follow from any action", other)),
+        }
+    }
+
+    /* Running totals of every seat after each score change, oldest first */
+    pub fn score_history(&self) -> Vec<[i32; 3]> {
+        let mut totals: [i32; 3] = [0, 1, 2].map(|seat| {
+            self.initial.players.get(seat).map_or(0, |p| p.score)
+        });
+        let mut history = vec![];
+        for event in &self.events {
+            if let Event::ScoreChanged { seat, total, .. } = event {
+                totals[*seat] = *total;
+                history.push(totals);
+            }
+        }
+        history
+    }
+
+    pub fn to_json_lines(&self) -> String {
+        let mut text = serde_json::to_string(&self.initial).expect("Game state always serializes");
+        text.push('\n');
+        for event in &self.events {
+            text.push_str(&Self::event_line(event));
+        }
+        text
+    }
+
+    pub fn from_json_lines(text: &str) -> io::Result<Self> {
+        let invalid = |e: serde_json::Error| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        let first = lines
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Empty game log"))?;
+        let initial: GameEngine = serde_json::from_str(first).map_err(invalid)?;
+        let events = lines
+            .map(|l| serde_json::from_str(l).map_err(invalid))
+            .collect::<io::Result<Vec<Event>>>()?;
+        Ok(GameLog { initial, events })
+    }
+
+    fn event_line(event: &Event) -> String {
+        let mut line = serde_json::to_string(event).expect("Events always serialize");
+        line.push('\n');
+        line
+    }
+
+    /* Start the file over with the starting engine and the events so far */
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json_lines())
+    }
+
+    /* Add events to a file written by `save` */
+    pub fn append(path: impl AsRef<Path>, events: &[Event]) -> io::Result<()> {
+        let mut file: File = OpenOptions::new().append(true).open(path)?;
+        for event in events {
+            file.write_all(Self::event_line(event).as_bytes())?;
+        }
+        file.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_json_lines(&fs::read_to_string(path)?)
+    }
+
+    /*
+        Log in the file when it leads up to `engine`, so a resumed match goes
+        on appending to it. A missing file is started at `engine`
+    */
+    pub fn resume(path: impl AsRef<Path>, engine: &GameEngine) -> io::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            let log = GameLog::new(engine);
+            log.save(path)?;
+            return Ok(log);
+        }
+        let log = Self::load(path)?;
+        let reached = log.replay().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let same = reached.pending_decision() == engine.pending_decision() &&
+            (0..engine.players.len()).all(|seat| reached.observation(seat) == engine.observation(seat));
+        if !same {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "the log does not lead to the loaded game"));
+        }
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::BotTable;
+
+    /* Random bots play `actions` decisions of a fresh match while it is recorded */
+    fn recorded_match(actions: usize) -> (GameEngine, GameLog) {
+        let mut table = BotTable::new(0);
+        table.play(actions);
+        (table.engine, table.log)
+    }
+
+    #[test]
+    fn replay_rebuilds_the_same_state() {
+        let (engine, log) = recorded_match(250);
+        let replayed = GameLog::from_json_lines(&log.to_json_lines()).unwrap().replay().unwrap();
+
+        for (a, b) in replayed.players.iter().zip(&engine.players) {
+            assert_eq!((&a.hand, a.score, a.by_contract), (&b.hand, b.score, b.by_contract));
+        }
+        assert_eq!(replayed.board.trick, engine.board.trick);
+        assert_eq!(replayed.board.history, engine.board.history);
+        assert_eq!(replayed.gone_cards(), engine.gone_cards());
+        assert_eq!(replayed.schedule.deals_played(), engine.schedule.deals_played());
+        assert_eq!(replayed.trump(), engine.trump());
+        assert_eq!(replayed.pending_decision(), engine.pending_decision());
+
+        let last = log.score_history().last().copied().unwrap();
+        assert_eq!(last, [0, 1, 2].map(|seat| engine.players[seat].score));
+    }
+
+    #[test]
+    fn forged_scores_are_caught() {
+        let (_, mut log) = recorded_match(100);
+        let index = log.events.iter().position(|e| matches!(e, Event::ScoreChanged { .. })).unwrap();
+        if let Event::ScoreChanged { delta, total, .. } = &mut log.events[index] {
+            *delta += 10;
+            *total += 10;
+        }
+        match log.replay() {
+            Err(error) => assert_eq!(error.index, index),
+            Ok(_) => panic!("Forged score was accepted"),
+        }
+    }
+
+    #[test]
+    fn a_resumed_match_goes_on_with_its_log() {
+        let path = std::env::temp_dir().join(format!("king-log-test-{}.jsonl", std::process::id()));
+        let mut table = BotTable::new(5);
+        table.play(60);
+        table.log.save(&path).unwrap();
+
+        let resumed = GameLog::resume(&path, &table.engine).unwrap();
+        assert_eq!(resumed.events, table.log.events);
+
+        table.play(1);
+        let error = GameLog::resume(&path, &table.engine).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(GameLog::load(&path).unwrap().events.len(), resumed.events.len());
+
+        fs::remove_file(&path).unwrap();
+        let started = GameLog::resume(&path, &table.engine).unwrap();
+        assert!(started.events.is_empty());
+        assert!(GameLog::load(&path).is_ok());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn partial_replay_stops_early() {
+        let (_, log) = recorded_match(40);
+        let deal_started = log.replay_to(2).unwrap();
+        assert_eq!(deal_started.schedule.deals_played(), 0);
+        assert_eq!(deal_started.players.iter().map(|p| p.hand.len()).sum::<usize>(), 32);
+        assert!(deal_started.gone_cards().is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+/*
+    Text notation for recorded matches, in the spirit of PGN. Headers come
+    first, then every deal as a few keyword lines. Seats are counted from 1.
+
+        [Seat1 "Ilia"]
+        [Seat2 "Sandro"]
+        [Seat3 "Levani"]
+        [Date "2026.10.18"]
+        [Seed "42"]
+        [Rules "K 2 + Q J + V H +"]
+
+        deal 1 K declarer 1
+        hands AC 9C ... | 10D AD ... | 8S QH ...
+        hidden 7D JS
+        trick 1:AS 2:KS 3:7S = 2
+        score 0 -40 0
+
+    `trump` follows `hidden` in the '+' games. `hands` lists the cards by seat
+    as dealt, `score` the running totals when the deal is over. `Rules` is
+    the contract of every row of the schedule. A log that starts in the middle
+    of a deal is written from the next deal on.
+*/
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::card::{Card, Suit};
+use crate::game::contract::Contract;
+use crate::game::engine::{Action, Decision, Event, GameEngine};
+use crate::game::log::GameLog;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrickRecord {
+    pub plays: Vec<(usize, Card)>, // seat and card in play order
+    pub winner: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DealRecord {
+    pub number: usize, // from 1
+    pub contract: Contract,
+    pub declarer: usize,
+    pub hands: [Vec<Card>; 3],
+    pub hidden: Option<[Card; 2]>,
+    pub trump: Option<Suit>,
+    pub tricks: Vec<TrickRecord>,
+    pub scores: Option<[i32; 3]>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Record {
+    pub headers: Vec<(String, String)>,
+    pub deals: Vec<DealRecord>,
+}
+
+impl Record {
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /* Replace a header or add it at the end */
+    pub fn set_header(&mut self, key: &str, value: &str) {
+        match self.headers.iter_mut().find(|(k, _)| k == key) {
+            Some(header) => header.1 = value.to_string(),
+            None => self.headers.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    /* Notation of a log dated today, see `from_log_at` */
+    pub fn from_log(log: &GameLog) -> Self {
+        Self::from_log_at(log, &today())
+    }
+
+    /* Notation of a log, with the seat names, seed and rules of its starting engine */
+    pub fn from_log_at(log: &GameLog, date: &str) -> Self {
+        let mut record = Record::default();
+        for (seat, player) in log.initial.players.iter().enumerate() {
+            record.set_header(&format!("Seat{}", seat + 1), &player.username);
+        }
+        record.set_header("Date", date);
+        if let Some(seed) = log.initial.seed() {
+            record.set_header("Seed", &seed.to_string());
+        }
+        record.set_header("Rules", &log.initial.schedule.rules());
+
+        /* The deal under way when the log starts is not recorded, nor counted twice */
+        let mut number = log.initial.schedule.deals_played();
+        if !matches!(log.initial.pending_decision(), Some(Decision::StartDeal) | None) {
+            number += 1;
+        }
+        let mut totals: [i32; 3] = [0, 1, 2].map(|s| log.initial.players.get(s).map_or(0, |p| p.score));
+        let mut trick: Vec<(usize, Card)> = vec![];
+
+        for event in &log.events {
+            let deal = record.deals.last_mut();
+            match (event, deal) {
+                (Event::DealStarted { contract, declarer }, _) => {
+                    number += 1;
+                    record.deals.push(DealRecord {
+                        number,
+                        contract: *contract,
+                        declarer: *declarer,
+                        hands: [vec![], vec![], vec![]],
+                        hidden: None,
+                        trump: None,
+                        tricks: vec![],
+                        scores: None,
+                    });
+                },
+                (Event::CardsDealt { hands }, Some(deal)) => deal.hands = hands.clone(),
+                (Event::CardsHidden { cards, .. }, Some(deal)) => deal.hidden = *cards,
+                (Event::TrumpChosen { trump, .. }, Some(deal)) => deal.trump = Some(*trump),
+                (Event::CardPlayed { seat, card }, Some(_)) => trick.push((*seat, *card)),
+                (Event::TrickWon { seat, .. }, Some(deal)) => {
+                    deal.tricks.push(TrickRecord { plays: trick.clone(), winner: *seat });
+                    trick.clear();
+                },
+                (Event::ScoreChanged { seat, total, .. }, _) => totals[*seat] = *total,
+                (Event::DealEnded, Some(deal)) => deal.scores = Some(totals),
+                _ => {}
+            }
+        }
+        record
+    }
+
+    /*
+        Play the record through a fresh engine, which checks every move, and
+        return the resulting log. The record has to start with the first deal.
+    */
+    pub fn to_log(&self) -> Result<GameLog, String> {
+        let mut engine = GameEngine::new();
+        for seat in 1..=3 {
+            let name = self.header(&format!("Seat{}", seat)).unwrap_or("?");
+            engine.add_player(name.to_string(), false).map_err(|e| e.to_string())?;
+        }
+        let mut log = GameLog::new(&engine);
+        if self.header("Rules").is_some_and(|rules| rules != engine.schedule.rules()) {
+            return Err(String::from("the record follows other rules"));
+        }
+
+        for deal in &self.deals {
+            let failed = |e: String| format!("deal {}: {}", deal.number, e);
+            if deal.number != engine.schedule.deals_played() + 1 {
+                return Err(failed(String::from("deals are not in schedule order")));
+            }
+
+            let mut actions = vec![];
+            if let Some(cards) = deal.hidden {
+                actions.push(Action::HideCards { seat: deal.declarer, cards });
+            }
+            if let Some(trump) = deal.trump {
+                actions.push(Action::ChooseTrump { seat: deal.declarer, trump });
+            }
+            for trick in &deal.tricks {
+                for (seat, card) in &trick.plays {
+                    actions.push(Action::PlayCard { seat: *seat, card: *card });
+                }
+            }
+
+            let mut hands = deal.hands.clone();
+            hands.rotate_left(engine.declarer());
+            engine.set_next_hands(hands);
+            log.record(&engine.apply(Action::StartDeal).map_err(|e| failed(e.to_string()))?);
+            for action in actions {
+                let events = engine.apply(action.clone()).map_err(|e| failed(format!("{:?}: {}", action, e)))?;
+                log.record(&events);
+            }
+
+            /* The record has to agree with what the engine made of it */
+            let recorded = Record::from_log(&log);
+            let replayed = recorded.deals.last().unwrap();
+            if replayed.contract != deal.contract || replayed.declarer != deal.declarer {
+                return Err(failed(String::from("contract or declarer does not follow the schedule")));
+            }
+            if replayed.tricks != deal.tricks {
+                return Err(failed(String::from("a trick winner does not follow from the cards")));
+            }
+            if deal.scores.is_some() && replayed.scores != deal.scores {
+                return Err(failed(String::from("scores do not follow from the tricks")));
+            }
+        }
+        Ok(log)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e: String| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards.iter().map(Card::to_string).collect::<Vec<_>>().join(" ")
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.headers {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{} \"{}\"]", key, value)?;
+        }
+        for deal in &self.deals {
+            writeln!(f)?;
+            writeln!(f, "deal {} {} declarer {}", deal.number, deal.contract, deal.declarer + 1)?;
+            let hands: Vec<String> = deal.hands.iter().map(|h| cards_text(h)).collect();
+            writeln!(f, "hands {}", hands.join(" | "))?;
+            if let Some(hidden) = deal.hidden {
+                writeln!(f, "hidden {}", cards_text(&hidden))?;
+            }
+            if let Some(trump) = deal.trump {
+                writeln!(f, "trump {}", trump)?;
+            }
+            for trick in &deal.tricks {
+                let plays: Vec<String> = trick.plays.iter().map(|(s, c)| format!("{}:{}", s + 1, c)).collect();
+                writeln!(f, "trick {} = {}", plays.join(" "), trick.winner + 1)?;
+            }
+            if let Some(scores) = deal.scores {
+                writeln!(f, "score {} {} {}", scores[0], scores[1], scores[2])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/* Seat written from 1 */
+fn parse_seat(text: &str) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(seat @ 1..=3) => Ok(seat - 1),
+        _ => Err(format!("'{}' is not a seat", text)),
+    }
+}
+
+fn parse_cards(text: &str) -> Result<Vec<Card>, String> {
+    text.split_whitespace().map(|c| c.parse().map_err(|e| format!("{}", e))).collect()
+}
+
+fn parse_header(line: &str) -> Result<(String, String), String> {
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or("header must be in brackets")?;
+    let (key, value) = inner.split_once(' ').ok_or("header without a value")?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or("header value must be quoted")?;
+
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next().ok_or("dangling backslash")? } else { c });
+    }
+    Ok((key.to_string(), unescaped))
+}
+
+fn parse_trick(text: &str) -> Result<TrickRecord, String> {
+    let (plays, winner) = text.split_once('=').ok_or("trick without a winner")?;
+    let plays = plays
+        .split_whitespace()
+        .map(|play| {
+            let (seat, card) = play.split_once(':').ok_or(format!("'{}' is not seat:card", play))?;
+            Ok((parse_seat(seat)?, card.parse().map_err(|e| format!("{}", e))?))
+        })
+        .collect::<Result<Vec<(usize, Card)>, String>>()?;
+    Ok(TrickRecord { plays, winner: parse_seat(winner.trim())? })
+}
+
+fn parse_deal_line(rest: &str) -> Result<DealRecord, String> {
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    match words[..] {
+        [number, contract, "declarer", declarer] => Ok(DealRecord {
+            number: number.parse().map_err(|_| format!("'{}' is not a deal number", number))?,
+            contract: contract.parse()?,
+            declarer: parse_seat(declarer)?,
+            hands: [vec![], vec![], vec![]],
+            hidden: None,
+            trump: None,
+            tricks: vec![],
+            scores: None,
+        }),
+        _ => Err(String::from("expected 'deal <number> <contract> declarer <seat>'")),
+    }
+}
+
+impl FromStr for Record {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut record = Record::default();
+
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            let failed = |e: String| format!("line {}: {}", number + 1, e);
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                if !record.deals.is_empty() {
+                    return Err(failed(String::from("headers must come before the deals")));
+                }
+                record.headers.push(parse_header(line).map_err(|e| failed(e.to_string()))?);
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            if keyword == "deal" {
+                record.deals.push(parse_deal_line(rest).map_err(failed)?);
+                continue;
+            }
+            let deal = record.deals.last_mut().ok_or_else(|| failed(format!("'{}' before any deal", keyword)))?;
+            match keyword {
+                "hands" => {
+                    let hands = rest.split('|').map(parse_cards).collect::<Result<Vec<_>, _>>().map_err(failed)?;
+                    deal.hands = hands.try_into().map_err(|_| failed(String::from("expected three hands")))?;
+                },
+                "hidden" => {
+                    let cards = parse_cards(rest).map_err(failed)?;
+                    deal.hidden = Some(cards.try_into().map_err(|_| failed(String::from("expected two cards")))?);
+                },
+                "trump" => deal.trump = Some(rest.trim().parse().map_err(|e| failed(format!("{}", e)))?),
+                "trick" => deal.tricks.push(parse_trick(rest).map_err(failed)?),
+                "score" => {
+                    let scores = rest
+                        .split_whitespace()
+                        .map(|s| s.parse::<i32>().map_err(|_| format!("'{}' is not a score", s)))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(failed)?;
+                    deal.scores = Some(scores.try_into().map_err(|_| failed(String::from("expected three scores")))?);
+                },
+                other => return Err(failed(format!("unknown keyword '{}'", other))),
+            }
+        }
+        Ok(record)
+    }
+}
+
+/* Current UTC date as YYYY.MM.DD */
+fn today() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    date_of(seconds / 86_400)
+}
+
+/* Date `days` after 1970-01-01, counted in 400-year eras starting in March */
+fn date_of(days: u64) -> String {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::BotTable;
+
+    const DATE: &str = "2026.10.18";
+
+    fn table() -> BotTable {
+        BotTable::with_names(11, ["Ilia", "Sandro \"Sasha\"", "Levani"])
+    }
+
+    /* Log of random bots playing a seeded match until `deals` deals are over */
+    fn recorded(deals: usize) -> GameLog {
+        let mut table = table();
+        table.play_deals(deals);
+        table.log
+    }
+
+    #[test]
+    fn notation_round_trips() {
+        let record = Record::from_log_at(&recorded(9), DATE);
+        let text = record.to_string();
+
+        assert_eq!(text.parse::<Record>(), Ok(record.clone()));
+        assert_eq!(record.header("Seat2"), Some("Sandro \"Sasha\""));
+        assert_eq!(record.header("Seed"), Some("11"));
+        assert_eq!(record.header("Rules"), Some("K 2 + Q J + V H +"));
+        assert!(text.contains("[Date \"2026.10.18\"]"));
+        assert_eq!(record.deals.len(), 9);
+        assert!(record.deals.iter().filter(|d| d.contract == Contract::Plus).all(|d| d.trump.is_some()));
+        assert!(text.contains("deal 7 + declarer 1"));
+    }
+
+    #[test]
+    fn a_log_resumed_mid_deal_starts_with_the_next_deal() {
+        let mut table = table();
+        table.play_until(|engine| engine.schedule.deals_played() == 1 && engine.current_trick().len() == 2);
+        table.log = GameLog::new(&table.engine);
+        table.play_deals(4);
+
+        let resumed = Record::from_log_at(&table.log, DATE);
+        let whole = Record::from_log_at(&recorded(4), DATE);
+        assert_eq!(resumed.deals, whole.deals[2..]);
+        assert_eq!(resumed.deals[0].number, 3);
+    }
+
+    #[test]
+    fn other_rules_are_rejected() {
+        let mut record = Record::from_log_at(&recorded(1), DATE);
+        assert!(record.to_log().is_ok());
+        record.set_header("Rules", "standard");
+        assert_eq!(record.to_log().err(), Some(String::from("the record follows other rules")));
+    }
+
+    #[test]
+    fn dates_count_days_from_the_epoch() {
+        assert_eq!(date_of(0), "1970.01.01");
+        assert_eq!(date_of(11_016), "2000.02.29");
+        assert_eq!(date_of(20_744), "2026.10.18");
+    }
+
+    #[test]
+    fn notation_plays_back_into_the_same_log() {
+        let log = recorded(6);
+        let rebuilt = Record::from_log(&log).to_string().parse::<Record>().unwrap().to_log().unwrap();
+        assert_eq!(rebuilt.events, log.events);
+        assert!(rebuilt.replay().is_ok());
+    }
+
+    #[test]
+    fn wrong_winner_is_rejected() {
+        let mut record = Record::from_log(&recorded(1));
+        let trick = &mut record.deals[0].tricks[0];
+        trick.winner = (trick.winner + 1) % 3;
+        match record.to_log() {
+            Err(error) => assert!(error.contains("winner"), "{}", error),
+            Ok(_) => panic!("Wrong winner was accepted"),
+        }
+    }
+
+    #[test]
+    fn malformed_lines_are_reported() {
+        assert!("hidden 7H 8H".parse::<Record>().unwrap_err().contains("before any deal"));
+        assert!("deal 1 X declarer 1".parse::<Record>().is_err());
+        assert!("deal 1 K declarer 4".parse::<Record>().is_err());
+        assert!("deal 1 K declarer 1\ntrick 1:AS 2:KS".parse::<Record>().is_err());
+        assert!("[Seat1 Ilia]".parse::<Record>().is_err());
+    }
+}
@@ -0,0 +1,251 @@
+/*
+    The part of the game one seat is allowed to see: its own hand and what
+    happened on the table, never the cards of the other seats. Controllers
+    get an `Observation` and the events as `redact` leaves them, never the
+    engine, so the redaction rules live here and nowhere else.
+*/
+
+use std::fmt;
+
+use crate::game::card::{Card, Suit};
+use crate::game::contract::Contract;
+use crate::game::engine::{Decision, Event, GameEngine};
+
+/* A finished trick as everybody saw it */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrickSeen {
+    pub leader: usize,
+    pub cards: Vec<Card>, // in play order, from the leader
+    pub winner: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Observation {
+    pub seat: usize,
+    pub names: Vec<String>,
+    pub hand: Vec<Card>,
+    pub contract: Option<Contract>, // None once the match is over
+    pub declarer: usize,
+    pub trump: Option<Suit>,
+    pub trick: Vec<(usize, Card)>, // unfinished trick, seat and card
+    pub played: Vec<Card>,         // cards of the deal in play order
+    pub tricks: Vec<TrickSeen>,    // finished tricks of the deal
+    pub hidden: Option<[Card; 2]>, // the declarer's own discards, None for everyone else
+    pub scores: Vec<i32>,
+    pub hand_sizes: Vec<usize>, // cards every seat holds, the table can count them
+    pub deals_played: usize,
+    pub decision: Option<Decision>, // what the engine waits for, whoever has to answer
+    pub legal_moves: Vec<Card>,      // cards the seat may play now
+    pub discard_pairs: Vec<[Card; 2]>, // pairs the seat may hide now
+}
+
+impl Observation {
+    pub fn of(engine: &GameEngine, seat: usize) -> Self {
+        Observation {
+            seat,
+            names: engine.players.iter().map(|p| p.username.clone()).collect(),
+            hand: engine.players[seat].hand.clone(),
+            contract: engine.contract(),
+            declarer: engine.declarer(),
+            trump: engine.trump(),
+            trick: engine.current_trick(),
+            played: engine.gone_cards(),
+            tricks: Self::tricks(engine),
+            hidden: engine.talon().filter(|(declarer, _)| *declarer == seat).map(|(_, cards)| cards),
+            scores: engine.players.iter().map(|p| p.score).collect(),
+            hand_sizes: engine.players.iter().map(|p| p.hand.len()).collect(),
+            deals_played: engine.schedule.deals_played(),
+            decision: engine.pending_decision(),
+            legal_moves: engine.legal_moves(seat),
+            discard_pairs: engine.legal_discard_pairs(seat),
+        }
+    }
+
+    /* Cards that belong to some pair the seat may hide, in hand order */
+    pub fn discardable(&self) -> Vec<Card> {
+        self.hand.iter().copied().filter(|c| self.discard_pairs.iter().any(|pair| pair.contains(c))).collect()
+    }
+
+    fn tricks(engine: &GameEngine) -> Vec<TrickSeen> {
+        engine.board.history
+            .iter()
+            .map(|trick| TrickSeen {
+                leader: trick.leader,
+                cards: trick.cards(),
+                winner: trick.winner(engine.trump()).map_or(trick.leader, |(seat, _)| seat),
+            })
+            .collect()
+    }
+
+    /* One line per piece of the view, shared by the full view and the console commands */
+    pub fn score_line(&self) -> String {
+        let scores: Vec<String> = self.names.iter().zip(&self.scores).map(|(n, s)| format!("{} {}", n, s)).collect();
+        format!("Scores: {}", scores.join(", "))
+    }
+
+    pub fn trick_line(&self) -> String {
+        let trick: Vec<String> = self.trick.iter().map(|(seat, card)| format!("{} {}", self.names[*seat], card)).collect();
+        format!("On the table: {}", if trick.is_empty() { String::from("-") } else { trick.join(", ") })
+    }
+
+    pub fn hand_line(&self) -> String {
+        let hand: Vec<String> = self.hand.iter().enumerate().map(|(i, c)| format!("{}:{}", i, c)).collect();
+        format!("{} hand: {}", self.names[self.seat], hand.join(" "))
+    }
+
+    /* Finished tricks of the deal, who led and who took them */
+    pub fn history_lines(&self) -> Vec<String> {
+        self.tricks
+            .iter()
+            .enumerate()
+            .map(|(i, trick)| {
+                let cards: Vec<String> = trick.cards.iter().map(Card::to_string).collect();
+                format!(
+                    "Trick {}: {} led {}, {} took it",
+                    i + 1,
+                    self.names[trick.leader],
+                    cards.join(" "),
+                    self.names[trick.winner]
+                )
+            })
+            .collect()
+    }
+}
+
+/* The event as `seat` may learn it, without the other hands and without cards somebody else hid */
+pub fn redact(event: &Event, seat: usize) -> Event {
+    match event {
+        Event::CardsDealt { hands } => Event::CardsDealt {
+            hands: [0, 1, 2].map(|s| if s == seat { hands[s].clone() } else { vec![] }),
+        },
+        Event::CardsHidden { seat: declarer, .. } if *declarer != seat => {
+            Event::CardsHidden { seat: *declarer, cards: None }
+        },
+        other => other.clone(),
+    }
+}
+
+/* What the seat sees at its turn, hand cards are numbered for the console */
+impl fmt::Display for Observation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(contract) = self.contract {
+            writeln!(f, "<-------------------{} ({} declares)------------------->", contract.name(), self.names[self.declarer])?;
+        }
+        if let Some(trump) = self.trump {
+            writeln!(f, "Trump is {}", trump)?;
+        }
+        writeln!(f, "{}", self.score_line())?;
+
+        let played: Vec<String> = self.played.iter().map(Card::to_string).collect();
+        writeln!(f, "Played this deal: {}", if played.is_empty() { String::from("-") } else { played.join(" ") })?;
+        if let Some(hidden) = self.hidden {
+            writeln!(f, "You hid {} {}", hidden[0], hidden[1])?;
+        }
+        writeln!(f, "{}", self.trick_line())?;
+        writeln!(f, "{}", self.hand_line())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::engine::Event;
+    use crate::game::testing::BotTable;
+
+    #[test]
+    fn only_the_own_hand_is_visible() {
+        let mut table = BotTable::new(11);
+        table.play_until(|engine| engine.current_trick().len() == 2);
+        let engine = table.engine;
+
+        for seat in 0..3 {
+            let observation = engine.observation(seat);
+            let text = observation.to_string();
+            assert_eq!(observation.hand, engine.players[seat].hand);
+            for other in (0..3).filter(|s| *s != seat) {
+                for card in &engine.players[other].hand {
+                    assert!(!observation.hand.contains(card));
+                    assert!(!text.contains(&format!(":{}", card)));
+                }
+            }
+        }
+
+        let trick = engine.current_trick();
+        assert_eq!(trick.len(), 2);
+        assert_eq!(trick, engine.board.trick.plays);
+        assert_eq!(trick[0].0, engine.declarer());
+        assert_eq!(trick[1].0, (engine.declarer() + 1) % 3);
+    }
+
+    #[test]
+    fn tricks_and_discards_as_the_table_saw_them() {
+        let mut table = BotTable::new(4);
+        let mut won = vec![];
+        let mut hidden = None;
+        for event in table.play_until(|engine| engine.board.history.len() == 3) {
+            match event {
+                Event::DealStarted { .. } => won.clear(),
+                Event::CardsHidden { cards, .. } => hidden = cards,
+                Event::TrickWon { seat, cards, .. } => won.push((seat, cards)),
+                _ => {}
+            }
+        }
+        let engine = table.engine;
+
+        let declarer = engine.declarer();
+        for seat in 0..3 {
+            let observation = engine.observation(seat);
+            assert_eq!(observation.hidden, if seat == declarer { hidden } else { None });
+            let seen: Vec<(usize, Vec<Card>)> = observation.tricks.iter().map(|t| (t.winner, t.cards.clone())).collect();
+            assert_eq!(seen, won);
+        }
+        let tricks = engine.observation(0).tricks;
+        assert_eq!(tricks[0].leader, declarer);
+        for pair in tricks.windows(2) {
+            assert_eq!(pair[1].leader, pair[0].winner);
+        }
+    }
+
+    #[test]
+    fn discards_stay_with_the_seat_that_hid_them() {
+        let mut table = BotTable::new(4);
+        let hidden_by = |engine: &GameEngine| (0..3).map(|s| engine.observation(s).hidden).collect::<Vec<_>>();
+        let first = table.play_deals(1).into_iter().find_map(|event| match event {
+            Event::CardsHidden { cards, .. } => cards,
+            _ => None,
+        });
+
+        /* The next declarer is already known but did not hide anything yet */
+        assert_eq!(table.engine.declarer(), 1);
+        assert_eq!(hidden_by(&table.engine), vec![first, None, None]);
+
+        table.play(1);
+        assert_eq!(hidden_by(&table.engine), vec![None, None, None]);
+
+        let Some(Event::CardsHidden { cards, .. }) = table.step().unwrap().first().cloned() else {
+            panic!("The declarer hides first")
+        };
+        assert_eq!(hidden_by(&table.engine), vec![None, cards, None]);
+    }
+
+    #[test]
+    fn events_keep_other_hands_and_discards_out() {
+        let mut table = BotTable::new(4);
+        let mut events = table.step().unwrap();
+        events.extend(table.step().unwrap());
+        let [Event::DealStarted { declarer, .. }, Event::CardsDealt { hands }, Event::CardsHidden { cards, .. }] = &events[..] else {
+            panic!("A deal starts with its cards and the discards")
+        };
+
+        for seat in 0..3 {
+            let seen: Vec<Event> = events.iter().map(|event| redact(event, seat)).collect();
+            assert_eq!(seen[0], events[0]);
+            let Event::CardsDealt { hands: own } = &seen[1] else { unreachable!() };
+            for other in 0..3 {
+                assert_eq!(own[other], if other == seat { hands[other].clone() } else { vec![] });
+            }
+            let hidden = if seat == *declarer { *cards } else { None };
+            assert_eq!(seen[2], Event::CardsHidden { seat: *declarer, cards: hidden });
+        }
+    }
+}
@@ -0,0 +1,246 @@
+/*
+    Replay viewer for recorded matches. A recording is a game log or a file in
+    the text notation; the viewer walks it trick by trick and renders every
+    position as plain text, so any front end can show it.
+*/
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::card::Card;
+use crate::game::contract::Contract;
+use crate::game::engine::Event;
+use crate::game::log::{GameLog, ReplayError};
+use crate::game::notation::Record;
+
+/* A position the viewer can stop at */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    pub events: usize, // how much of the log is replayed
+    pub deal: usize,   // from 1
+    pub trick: usize,  // tricks done in this deal
+    pub won: Option<(usize, Card, Vec<Card>)>, // seat, winning card and the trick just taken
+}
+
+pub struct Replay {
+    log: GameLog,
+    frames: Vec<Frame>,
+    position: usize,
+    pub visible: [bool; 3],
+}
+
+/* Game log or text notation, told apart by the first character */
+pub fn load_recording(path: impl AsRef<Path>) -> io::Result<GameLog> {
+    let text = fs::read_to_string(path)?;
+    if text.trim_start().starts_with('{') {
+        return GameLog::from_json_lines(&text);
+    }
+    text.parse::<Record>()
+        .and_then(|record| record.to_log())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl Replay {
+    pub fn new(log: GameLog) -> Self {
+        let mut frames = vec![];
+        let mut deal = log.initial.schedule.deals_played();
+        let mut trick = 0;
+        let mut started = false;
+
+        for (index, event) in log.events.iter().enumerate() {
+            match event {
+                Event::DealStarted { .. } => {
+                    deal += 1;
+                    trick = 0;
+                    started = false;
+                },
+                /* Everything is dealt, hidden and announced, no card played yet */
+                Event::CardPlayed { .. } if !started => {
+                    started = true;
+                    frames.push(Frame { events: index, deal, trick, won: None });
+                },
+                Event::TrickWon { seat, card, cards } => {
+                    trick += 1;
+                    frames.push(Frame { events: index + 1, deal, trick, won: Some((*seat, *card, cards.clone())) });
+                },
+                _ => {}
+            }
+        }
+        Replay { log, frames, position: 0, visible: [true; 3] }
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn forward(&mut self) {
+        if self.position + 1 < self.frames.len() {
+            self.position += 1;
+        }
+    }
+
+    pub fn back(&mut self) {
+        self.position = self.position.saturating_sub(1);
+    }
+
+    /* Go to the start of a deal, false when the recording does not have it */
+    pub fn jump_to_deal(&mut self, deal: usize) -> bool {
+        match self.frames.iter().position(|f| f.deal == deal) {
+            Some(position) => {
+                self.position = position;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn toggle(&mut self, seat: usize) {
+        if seat < 3 {
+            self.visible[seat] = !self.visible[seat];
+        }
+    }
+
+    /* Apply one typed command, false once the viewer should close */
+    pub fn command(&mut self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            [] | ["n"] | ["next"] => self.forward(),
+            ["b"] | ["back"] => self.back(),
+            ["d", deal] | ["deal", deal] => {
+                if !deal.parse().is_ok_and(|d| self.jump_to_deal(d)) {
+                    println!("No deal {} in this recording", deal);
+                }
+            },
+            ["v", seat] | ["view", seat] => match seat.parse::<usize>() {
+                Ok(seat @ 1..=3) => self.toggle(seat - 1),
+                _ => println!("Seats are 1, 2 and 3"),
+            },
+            ["q"] | ["quit"] => return false,
+            _ => println!("{}", Self::help()),
+        }
+        true
+    }
+
+    pub fn help() -> &'static str {
+        "Enter or n: next trick, b: back, d <n>: jump to deal, v <seat>: show or hide a hand, q: quit"
+    }
+
+    pub fn render(&self) -> Result<String, ReplayError> {
+        let frame = match self.frames.get(self.position) {
+            Some(frame) => frame,
+            None => return Ok(String::from("Nothing was played in this recording\n")),
+        };
+        let engine = self.log.replay_to(frame.events)?;
+        let players = &engine.players;
+        let declarer = self.frame_declarer(frame);
+        let mut text = String::new();
+
+        let contract = self.frame_contract(frame).map_or("?", |c| c.name());
+        text.push_str(&format!(
+            "<-------------------Deal {}: {} ({} declares)------------------->\n",
+            frame.deal, contract, players[declarer].username
+        ));
+        if let Some(trump) = engine.trump() {
+            text.push_str(&format!("Trump is {}\n", trump));
+        }
+
+        match &frame.won {
+            Some((winner, card, cards)) => {
+                let trick: Vec<String> = cards.iter().map(Card::to_string).collect();
+                text.push_str(&format!("Trick {}: {}\n", frame.trick, trick.join(" ")));
+                text.push_str(&format!("Winner card is {}, it goes to {}\n", card, players[*winner].username));
+            },
+            None => text.push_str("Before the first trick\n"),
+        }
+
+        for (seat, player) in players.iter().enumerate() {
+            let mut hand = player.hand.clone();
+            hand.sort_by_key(|c| (c.suit, c.rank));
+            let cards = if self.visible[seat] {
+                hand.iter().map(Card::to_string).collect::<Vec<_>>().join(" ")
+            } else {
+                format!("({} cards)", hand.len())
+            };
+            text.push_str(&format!("{} [{}]: {}\n", player.username, player.score, cards));
+        }
+        text.push_str(&format!("Frame {} of {}\n", self.position + 1, self.frames.len()));
+        Ok(text)
+    }
+
+    fn frame_deal_start(&self, frame: &Frame) -> Option<&Event> {
+        self.log.events[..frame.events]
+            .iter()
+            .rev()
+            .find(|e| matches!(e, Event::DealStarted { .. }))
+    }
+
+    fn frame_declarer(&self, frame: &Frame) -> usize {
+        match self.frame_deal_start(frame) {
+            Some(Event::DealStarted { declarer, .. }) => *declarer,
+            _ => 0,
+        }
+    }
+
+    fn frame_contract(&self, frame: &Frame) -> Option<Contract> {
+        match self.frame_deal_start(frame) {
+            Some(Event::DealStarted { contract, .. }) => Some(*contract),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::BotTable;
+
+    fn recorded(deals: usize) -> GameLog {
+        let mut table = BotTable::with_names(5, ["Ilia", "Sandro", "Levani"]);
+        table.play_deals(deals);
+        table.log
+    }
+
+    #[test]
+    fn frames_stop_before_the_deal_and_after_every_trick() {
+        let log = recorded(3);
+        let tricks = log.events.iter().filter(|e| matches!(e, Event::TrickWon { .. })).count();
+        let replay = Replay::new(log);
+        assert_eq!(replay.frames().len(), 3 + tricks);
+        assert_eq!(replay.frames()[0].won, None);
+        assert_eq!(replay.frames()[1].trick, 1);
+    }
+
+    #[test]
+    fn stepping_and_jumping() {
+        let mut replay = Replay::new(recorded(3));
+        replay.back();
+        assert_eq!(replay.position(), 0);
+        replay.command("n");
+        replay.command("");
+        assert_eq!(replay.position(), 2);
+        replay.command("b");
+        assert_eq!(replay.position(), 1);
+
+        assert!(replay.jump_to_deal(3));
+        assert_eq!(replay.frames()[replay.position()].deal, 3);
+        assert!(!replay.jump_to_deal(4));
+        assert!(!replay.command("q"));
+    }
+
+    #[test]
+    fn hidden_seats_only_show_their_card_count() {
+        let mut replay = Replay::new(recorded(1));
+        let shown = replay.render().unwrap();
+        replay.command("v 2");
+        let hidden = replay.render().unwrap();
+
+        assert!(shown.contains("Deal 1: King (Ilia declares)"));
+        assert!(hidden.contains("Sandro [0]: (10 cards)"));
+        assert!(!shown.contains("(10 cards)"));
+    }
+}
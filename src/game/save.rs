@@ -0,0 +1,102 @@
+/*
+    Saved matches. The whole engine goes to a JSON file, so a match can be
+    resumed at any point, even in the middle of a trick. Loading is left to
+    the command line on purpose, the seats of a loaded match are picked there.
+*/
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::engine::GameEngine;
+
+/* Bumped whenever the saved engine state changes shape */
+pub const SAVE_VERSION: u32 = 3;
+
+/* File written after every trick by the console game */
+pub const AUTOSAVE_FILE: &str = "king-autosave.json";
+
+#[derive(Deserialize, Serialize)]
+struct SavedGame {
+    version: u32,
+    engine: GameEngine,
+}
+
+pub fn to_json(engine: &GameEngine) -> String {
+    let saved = SavedGame { version: SAVE_VERSION, engine: engine.clone() };
+    serde_json::to_string_pretty(&saved).expect("Game state always serializes")
+}
+
+pub fn from_json(json: &str) -> io::Result<GameEngine> {
+    let saved: SavedGame = serde_json::from_str(json)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if saved.version != SAVE_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Save file version {} is not supported", saved.version),
+        ));
+    }
+    Ok(saved.engine)
+}
+
+pub fn save_game(engine: &GameEngine, path: impl AsRef<Path>) -> io::Result<()> {
+    /* Write next to the target first so a crash never leaves half a file */
+    let path = path.as_ref();
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, to_json(engine))?;
+    fs::rename(&temporary, path)
+}
+
+pub fn load_game(path: impl AsRef<Path>) -> io::Result<GameEngine> {
+    from_json(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::engine::Decision;
+    use crate::game::testing::BotTable;
+
+    /* Random play until the third deal (the first with trumps) is in the middle of a trick */
+    fn engine_mid_trick() -> GameEngine {
+        let mut table = BotTable::new(0);
+        table.play_until(|engine| engine.trump().is_some() && engine.board.trick.len() == 2);
+        table.engine
+    }
+
+    #[test]
+    fn saved_game_resumes_mid_trick() {
+        let engine = engine_mid_trick();
+        let path = std::env::temp_dir().join(format!("king-save-test-{}.json", std::process::id()));
+        save_game(&engine, &path).unwrap();
+        let loaded = load_game(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(to_json(&loaded), to_json(&engine));
+        for (a, b) in loaded.players.iter().zip(&engine.players) {
+            assert_eq!((&a.username, &a.hand, a.score), (&b.username, &b.hand, b.score));
+        }
+        assert_eq!(loaded.board.trick, engine.board.trick);
+        assert_eq!(loaded.board.history, engine.board.history);
+        assert_eq!(loaded.gone_cards(), engine.gone_cards());
+        assert_eq!(loaded.schedule.deals_played(), engine.schedule.deals_played());
+        assert_eq!(loaded.trump(), engine.trump());
+        let seat = match loaded.pending_decision() {
+            Some(Decision::PlayCard { seat }) => seat,
+            other => panic!("Expected a card to be played, got {:?}", other),
+        };
+        assert_eq!(loaded.legal_moves(seat), engine.legal_moves(seat));
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let json = to_json(&GameEngine::new()).replacen(
+            &format!("\"version\": {}", SAVE_VERSION),
+            "\"version\": 999",
+            1,
+        );
+        assert!(from_json(&json).is_err());
+    }
+}
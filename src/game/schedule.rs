@@ -1,7 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::contract::Contract;
+
+/* Nine rows of games, every player declares each of them once */
+pub const DEALS: usize = 27;
+
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Schedule {
     main_player_idx: usize,
     game_type_idx: usize,
-    game_types: [[char; 3]; 9]
+    game_types: [[Contract; 3]; 9]
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Schedule {
@@ -10,30 +24,49 @@ impl Schedule {
             main_player_idx: 0,
             game_type_idx: 0,
             game_types: [
-                ['K','K','K'],
-                ['2','2','2'], 
-                ['+','+','+'], 
-                ['Q','Q','Q'], 
-                ['J','J','J'], 
-                ['+','+','+'], 
-                ['V','V','V'], 
-                ['H','H','H'], 
-                ['+','+','+']
+                [Contract::King; 3],
+                [Contract::LastTwo; 3],
+                [Contract::Plus; 3],
+                [Contract::Queens; 3],
+                [Contract::Jacks; 3],
+                [Contract::Plus; 3],
+                [Contract::Tricks; 3],
+                [Contract::Hearts; 3],
+                [Contract::Plus; 3]
             ]
         }
     }
 
-    pub fn get_game(&mut self) -> char {
-        self.game_types[self.game_type_idx][self.main_player_idx]
+    /* None once the schedule is over */
+    pub fn get_game(&self) -> Option<Contract> {
+        self.game_types.get(self.game_type_idx).map(|row| row[self.main_player_idx])
     }
 
-    pub fn next_game(&mut self) -> char {
+    pub fn next_game(&mut self) {
         if self.main_player_idx == 2 {
             self.main_player_idx = 0;
             self.game_type_idx += 1;
         } else {
             self.main_player_idx += 1;
         }
-        self.get_game()
     }
-}
\ No newline at end of file
+
+    /* Seat declaring the current deal, it moves one seat on every deal */
+    pub fn declarer(&self) -> usize {
+        self.main_player_idx
+    }
+
+    pub fn deals_played(&self) -> usize {
+        self.game_type_idx * 3 + self.main_player_idx
+    }
+
+    /* Contract of every row in playing order, the codes split by spaces */
+    pub fn rules(&self) -> String {
+        self.game_types.iter().map(|row| row[0].code().to_string()).collect::<Vec<_>>().join(" ")
+    }
+
+    /* True after the last '+' deal */
+    pub fn is_over(&self) -> bool {
+        self.game_type_idx >= self.game_types.len()
+    }
+}
@@ -0,0 +1,62 @@
+/*
+    Shared test table: three seeded random bots playing a seeded match while
+    it is recorded, so tests can get to any point of a match in one line.
+*/
+
+use crate::bots::RandomBot;
+use crate::game::controller;
+use crate::game::engine::{Event, GameEngine};
+use crate::game::log::GameLog;
+
+pub struct BotTable {
+    pub engine: GameEngine,
+    pub log: GameLog, // everything played since the table was set
+    bots: Vec<RandomBot>,
+}
+
+impl BotTable {
+    /* Players A, B and C */
+    pub fn new(seed: u64) -> Self {
+        Self::with_names(seed, ["A", "B", "C"])
+    }
+
+    pub fn with_names(seed: u64, names: [&str; 3]) -> Self {
+        let mut engine = GameEngine::new().with_seed(seed);
+        for name in names {
+            engine.add_player(String::from(name), false).unwrap();
+        }
+        BotTable {
+            log: GameLog::new(&engine),
+            engine,
+            bots: (0..3).map(RandomBot::with_seed).collect(),
+        }
+    }
+
+    /* Let the bots take the pending decision, None once the match is over */
+    pub fn step(&mut self) -> Option<Vec<Event>> {
+        let decision = self.engine.pending_decision()?;
+        let events = controller::ask_until_accepted(&mut self.engine, &mut self.bots, decision).unwrap();
+        self.log.record(&events);
+        Some(events)
+    }
+
+    pub fn play(&mut self, decisions: usize) {
+        for _ in 0..decisions {
+            self.step().expect("The match ended too early");
+        }
+    }
+
+    /* Play until `done` holds for the engine, returns the events on the way */
+    pub fn play_until(&mut self, mut done: impl FnMut(&GameEngine) -> bool) -> Vec<Event> {
+        let mut events = vec![];
+        while !done(&self.engine) {
+            events.extend(self.step().expect("The match ended too early"));
+        }
+        events
+    }
+
+    /* Play until `deals` deals are over */
+    pub fn play_deals(&mut self, deals: usize) -> Vec<Event> {
+        self.play_until(|engine| engine.schedule.deals_played() >= deals)
+    }
+}
@@ -0,0 +1,19 @@
+/*
+    File: lib.rs
+    Title: King cards game engine
+    Author: Ilia Sichinava
+*/
+
+pub mod bots;
+pub mod duplicate;
+pub mod game;
+pub mod net;
+pub mod solver;
+pub mod tui;
+
+pub use game::card::{Card, Rank, Suit};
+pub use game::contract::Contract;
+pub use game::deal::DealId;
+pub use game::controller::{HotSeatController, PlayerController, StdinController};
+pub use game::engine::{Action, Decision, Event, GameError, GameEngine, RuleError};
+pub use game::observation::{Observation, TrickSeen};
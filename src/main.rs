@@ -13,22 +13,216 @@
     ✔️ Do not let lay down king heart first during its game
 
     ✔️ Finish all games 1D
-    TODO: Make multiplayer using sockets 1D
+    ✔️ Make multiplayer using sockets 1D
     TODO: Build front using Yew 1D
 
     Expected finish date -> January 6th
 */
 
 /* Game class imports */
-mod game;
-use game::main::Game;
+use std::cell::RefCell;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
+use king::bots;
+use king::game::deal::DealId;
+use king::game::main::Game;
+use king::game::notation::Record;
+use king::game::replay::{self, Replay};
+use king::game::save;
+use king::tui::{Screen, TuiController};
+use king::{GameError, HotSeatController, PlayerController, StdinController};
+
+/*
+    Every seat is "human" or one of `bots::BOT_NAMES`, given in seat order:
+        cargo run -- human heuristic random
+    Without arguments one human plays against two heuristic bots.
+    A saved match continues with `cargo run -- load <file> [seats]`, and
+    `cargo run -- replay <file>` steps through a log or notation file.
+    `--seed <number>` makes the whole match reproducible, `--deal <ID>`
+    starts with a deal printed by an earlier game, neither goes with `load`.
+    `--log <file>` records every event of the match, a loaded match goes on
+    in the log it was started in and every later match gets a numbered file
+    (game-2.log, ...). `--notation <file>` writes the match out in the
+    text notation when the match is over. `--tui` plays the human seats
+    full screen with the arrow keys, `--hot-seat` lets several humans share
+    the console and shows each of them only their own hand.
+*/
 fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("replay") {
+        match args.get(1) {
+            Some(path) => replay(path),
+            None => eprintln!("Usage: king replay <file>"),
+        }
+        return;
+    }
+    let seed: Option<u64> = match take_option(&mut args, "--seed").map(|s| s.parse()) {
+        Some(Ok(seed)) => Some(seed),
+        Some(Err(_)) => {
+            eprintln!("--seed needs a number");
+            return;
+        },
+        None => None,
+    };
+    let first_deal: Option<DealId> = match take_option(&mut args, "--deal").map(|s| s.parse()) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(err)) => {
+            eprintln!("{}", err);
+            return;
+        },
+        None => None,
+    };
+    let log_file = take_option(&mut args, "--log").map(PathBuf::from);
+    let tui = args.iter().any(|a| a == "--tui");
+    args.retain(|a| a != "--tui");
+    let hot_seat = args.iter().any(|a| a == "--hot-seat");
+    args.retain(|a| a != "--hot-seat");
+    let screen = Screen::shared();
+    let notation_file = take_option(&mut args, "--notation").map(PathBuf::from);
+
+    let saved = if args.first().map(String::as_str) == Some("load") {
+        if args.len() < 2 {
+            eprintln!("Usage: king load <file> [human|{}] x3", bots::BOT_NAMES.join("|"));
+            return;
+        }
+        if seed.is_some() || first_deal.is_some() {
+            eprintln!("--seed and --deal start a new match, a loaded one keeps its own deals");
+            return;
+        }
+        let path = args.remove(1);
+        args.remove(0);
+        match save::load_game(&path) {
+            Ok(engine) => Some(engine),
+            Err(err) => {
+                eprintln!("Could not load {}: {}", path, err);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut kinds = args;
+    if kinds.is_empty() {
+        kinds = vec![String::from("human"), String::from("heuristic"), String::from("heuristic")];
+    }
+    if kinds.len() != 3 {
+        eprintln!("Usage: king [human|{}] x3", bots::BOT_NAMES.join("|"));
+        return;
+    }
+
+    let spectator = kinds.iter().all(|kind| kind != "human");
+    let mut controllers: Vec<Box<dyn PlayerController>> = vec![];
+    for kind in &kinds {
+        if kind == "human" && tui {
+            controllers.push(Box::new(TuiController::new(Rc::clone(&screen))));
+        } else if kind == "human" && hot_seat {
+            controllers.push(Box::new(HotSeatController::new()));
+        } else if kind == "human" {
+            controllers.push(Box::new(StdinController::new()));
+        } else if let Some(bot) = bots::controller_by_name(kind) {
+            controllers.push(bot);
+        } else {
+            eprintln!("Unknown player kind: {}", kind);
+            return;
+        }
+    }
+    if let Some(engine) = saved {
+        let mut gm: Game = Game::resume(engine, controllers);
+        gm.log_file = log_file;
+        gm.print_events = !tui;
+        gm.spectator = spectator;
+        gm.print_scores();
+        play_matches(&mut gm, notation_file.as_deref(), &screen);
+        return;
+    }
+
+    let names = ["Ilia", "Sandro", "Levani"];
     let mut gm: Game = Game::new();
-    gm.new_player(String::from("Ilia"), true);
-    gm.new_player(String::from("Sandro"), false);
-    gm.new_player(String::from("Levani"), false);
+    if let Some(seed) = seed {
+        gm.engine = gm.engine.with_seed(seed);
+    }
+    if let Some(id) = first_deal {
+        gm.engine.set_next_deal(id);
+    }
+    gm.log_file = log_file;
+    gm.print_events = !tui;
+    gm.spectator = spectator;
+    for (seat, controller) in controllers.into_iter().enumerate() {
+        if let Err(err) = gm.new_player_with_controller(String::from(names[seat]), seat == 0, controller) {
+            eprintln!("{}", err);
+            return;
+        }
+    }
+
+    play_matches(&mut gm, notation_file.as_deref(), &screen);
+}
+
+/* Step through a game log or notation file typed commands at a time */
+fn replay(path: &str) {
+    let log = match replay::load_recording(path) {
+        Ok(log) => log,
+        Err(err) => {
+            eprintln!("Could not load {}: {}", path, err);
+            return;
+        }
+    };
+    let mut viewer = Replay::new(log);
+    println!("{}", Replay::help());
+    loop {
+        match viewer.render() {
+            Ok(text) => print!("{}", text),
+            Err(err) => {
+                eprintln!("The recording does not replay: {}", err);
+                return;
+            }
+        }
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line).unwrap_or(0) == 0 || !viewer.command(&line) {
+            return;
+        }
+    }
+}
+
+/* Remove `name <value>` from the arguments, an empty value when it is the last one */
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let idx = args.iter().position(|a| a == name)?;
+    args.remove(idx);
+    Some(if idx < args.len() { args.remove(idx) } else { String::new() })
+}
 
-    gm.print_players();
-    gm.start_game();
+/* Matches follow each other until the players quit */
+fn play_matches(gm: &mut Game, notation_file: Option<&Path>, screen: &RefCell<Screen>) {
+    loop {
+        gm.print_players();
+        match gm.start_game() {
+            Ok(()) => {},
+            Err(GameError::Quit) => {
+                /* The log is already written, keep the match and give the terminal back */
+                screen.borrow_mut().close();
+                match save::save_game(&gm.engine, save::AUTOSAVE_FILE) {
+                    Ok(()) => println!("Game saved to {}, load it to go on", save::AUTOSAVE_FILE),
+                    Err(err) => eprintln!("Could not save to {}: {}", save::AUTOSAVE_FILE, err),
+                }
+                return;
+            },
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            },
+        }
+        /* The full-screen view gives the terminal back for the plain prompts */
+        screen.borrow_mut().close();
+        if let Some(path) = notation_file {
+            if let Err(err) = Record::from_log(&gm.log).save(path) {
+                eprintln!("Could not write {}: {}", path.display(), err);
+            }
+        }
+        if !gm.prepare_next_match(gm.ask_next_match()) {
+            break;
+        }
+    }
 }
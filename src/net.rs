@@ -0,0 +1,6 @@
+/* Playing over the network: message schema, server host, clients and the WebSocket gateway */
+
+pub mod protocol;
+pub mod server;
+pub mod client;
+pub mod websocket;
@@ -0,0 +1,34 @@
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+
+use crate::net::protocol::{self, ClientMessage, ServerMessage};
+use crate::net::server::{Connection, TcpConnection};
+
+/* Client side of the line-delimited JSON protocol */
+pub struct Client {
+    connection: TcpConnection,
+}
+
+impl Client {
+    /* Connect and take a seat under the given name */
+    pub fn connect(address: impl ToSocketAddrs, name: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        let mut client = Client { connection: TcpConnection::new(stream)? };
+        client.send(&ClientMessage::Join { name: name.to_string() })?;
+        Ok(client)
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        self.connection.send_line(&protocol::encode(message))
+    }
+
+    /* Next message from the server, None once it closed the connection */
+    pub fn receive(&mut self) -> io::Result<Option<ServerMessage>> {
+        match self.connection.read_line()? {
+            Some(line) => protocol::decode(&line)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+/*
+    Line-delimited JSON messages between the server and its clients.
+    Every message is one JSON object on its own line with a "type" field,
+    cards travel in notation ("10H", "KS").
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::card::{Card, Suit};
+use crate::game::contract::Contract;
+use crate::game::engine::{Decision, Event, GameEngine};
+use crate::game::observation::{self, Observation};
+
+/* Sent by a client */
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ClientMessage {
+    Join { name: String },
+    Play { card: Card },
+    Hide { cards: [Card; 2] },
+    Trump { suit: Suit },
+}
+
+/* What the server asks the seat to do in `YourTurn` */
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Request {
+    Play,
+    Hide,
+    Trump,
+}
+
+/* Sent by the server, each client only ever gets its own hand */
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ServerMessage {
+    Welcome { seat: usize },
+    Join { seat: usize, name: String },
+    Deal { contract: Contract, declarer: usize, hand: Vec<Card> },
+    YourTurn { request: Request, legal: Vec<Card> },
+    CardsHidden {
+        seat: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cards: Option<[Card; 2]>,
+    },
+    TrumpChosen { seat: usize, suit: Suit },
+    Play { seat: usize, card: Card },
+    TrickWon { seat: usize, cards: Vec<Card> },
+    ScoreUpdate { seat: usize, delta: i32, total: i32 },
+    DealOver { scores: Vec<i32> },
+    GameOver { scores: Vec<i32> },
+    Error { message: String },
+}
+
+/* One line of JSON, newline included */
+pub fn encode<T: Serialize>(message: &T) -> String {
+    let mut line = serde_json::to_string(message).expect("Messages always serialize");
+    line.push('\n');
+    line
+}
+
+pub fn decode<'a, T: Deserialize<'a>>(line: &'a str) -> Result<T, String> {
+    serde_json::from_str(line.trim()).map_err(|e| e.to_string())
+}
+
+/* What the seat is allowed to learn from an engine event */
+pub fn event_message(seen: &Observation, event: &Event) -> Option<ServerMessage> {
+    let message = match observation::redact(event, seen.seat) {
+        Event::DealStarted { contract, declarer } => ServerMessage::Deal { contract, declarer, hand: seen.hand.clone() },
+        Event::CardsHidden { seat, cards } => ServerMessage::CardsHidden { seat, cards },
+        Event::TrumpChosen { seat, trump } => ServerMessage::TrumpChosen { seat, suit: trump },
+        Event::CardPlayed { seat, card } => ServerMessage::Play { seat, card },
+        Event::TrickWon { seat, cards, .. } => ServerMessage::TrickWon { seat, cards },
+        Event::ScoreChanged { seat, delta, total } => ServerMessage::ScoreUpdate { seat, delta, total },
+        Event::DealEnded => ServerMessage::DealOver { scores: seen.scores.clone() },
+        /* Every seat already got its own hand with the deal message */
+        Event::CardsDealt { .. } => return None,
+        /* The host sends its own game-over message once the loop stops */
+        Event::MatchOver { .. } => return None,
+    };
+    Some(message)
+}
+
+/* Prompt for the seat when the engine is waiting for it */
+pub fn request_message(seen: &Observation) -> Option<ServerMessage> {
+    let (request, legal) = match seen.decision? {
+        Decision::HideCards { seat } if seat == seen.seat => (Request::Hide, seen.discardable()),
+        Decision::ChooseTrump { seat } if seat == seen.seat => (Request::Trump, vec![]),
+        Decision::PlayCard { seat } if seat == seen.seat => (Request::Play, seen.legal_moves.clone()),
+        _ => return None,
+    };
+    Some(ServerMessage::YourTurn { request, legal })
+}
+
+pub fn scores(engine: &GameEngine) -> Vec<i32> {
+    engine.players.iter().map(|p| p.score).collect()
+}
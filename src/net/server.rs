@@ -0,0 +1,392 @@
+/*
+    Multiplayer host. Three clients join, each becomes a `RemoteSeat`
+    controller, and the engine validates whatever they send. A client that
+    drops out is replaced by the heuristic bot so the others can finish.
+*/
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::bots::HeuristicBot;
+use crate::game::card::{Card, Suit};
+use crate::game::controller::{self, PlayerController};
+use crate::game::engine::{Event, GameEngine, GameError};
+use crate::game::observation::Observation;
+use crate::net::protocol::{self, ClientMessage, ServerMessage};
+use crate::net::websocket::WebSocketConnection;
+
+/* How long a client that connected may take to join */
+pub const JOIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/* A line based link to one client */
+pub trait Connection {
+    fn send_line(&mut self, line: &str) -> io::Result<()>;
+
+    /* Next line without its newline, None once the client is gone */
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+}
+
+pub struct TcpConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl TcpConnection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(TcpConnection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+}
+
+impl Connection for TcpConnection {
+    fn send_line(&mut self, line: &str) -> io::Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end().to_string()))
+    }
+}
+
+/* Seat played by a client on the other end of a connection */
+pub struct RemoteSeat {
+    connection: Box<dyn Connection + Send>,
+    connected: bool,
+    fallback: HeuristicBot,
+}
+
+impl RemoteSeat {
+    pub fn new(connection: Box<dyn Connection + Send>) -> Self {
+        RemoteSeat { connection, connected: true, fallback: HeuristicBot::new() }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn send(&mut self, message: &ServerMessage) {
+        if self.connected && self.connection.send_line(&protocol::encode(message)).is_err() {
+            self.connected = false;
+        }
+    }
+
+    /* Next well-formed message, None when the client has left */
+    fn receive(&mut self) -> Option<ClientMessage> {
+        while self.connected {
+            match self.connection.read_line() {
+                Ok(Some(line)) => match protocol::decode(&line) {
+                    Ok(message) => return Some(message),
+                    Err(error) => self.send(&ServerMessage::Error { message: error }),
+                },
+                _ => self.connected = false,
+            }
+        }
+        None
+    }
+
+    /* Prompt the client and wait for an answer `pick` understands */
+    fn ask<T>(&mut self, seen: &Observation, pick: impl Fn(ClientMessage) -> Option<T>) -> Option<T> {
+        if let Some(request) = protocol::request_message(seen) {
+            self.send(&request);
+        }
+        while let Some(message) = self.receive() {
+            match pick(message) {
+                Some(answer) => return Some(answer),
+                None => self.send(&ServerMessage::Error {
+                    message: String::from("Unexpected message, answer the request first"),
+                }),
+            }
+        }
+        None
+    }
+}
+
+impl PlayerController for RemoteSeat {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        let answer = self.ask(seen, |m| match m {
+            ClientMessage::Play { card } => Some(card),
+            _ => None,
+        });
+        answer.map_or_else(|| self.fallback.choose_card(seen), Ok)
+    }
+
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError> {
+        let answer = self.ask(seen, |m| match m {
+            ClientMessage::Trump { suit } => Some(suit),
+            _ => None,
+        });
+        answer.map_or_else(|| self.fallback.choose_trump(seen), Ok)
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        let answer = self.ask(seen, |m| match m {
+            ClientMessage::Hide { cards } => Some(cards),
+            _ => None,
+        });
+        answer.map_or_else(|| self.fallback.choose_cards_to_hide(seen), Ok)
+    }
+
+    fn rejected(&mut self, _seen: &Observation, error: GameError) {
+        self.send(&ServerMessage::Error { message: error.to_string() });
+    }
+
+    fn notify(&mut self, seen: &Observation, event: &Event) {
+        if let Some(message) = protocol::event_message(seen, event) {
+            self.send(&message);
+        }
+    }
+}
+
+/* Wait until three clients have sent their join message, over TCP or the WebSocket listener */
+pub fn accept_players(
+    listener: &TcpListener,
+    websocket: Option<&TcpListener>,
+) -> io::Result<Vec<(Box<dyn Connection + Send>, String)>> {
+    listener.set_nonblocking(true)?;
+    if let Some(websocket) = websocket {
+        websocket.set_nonblocking(true)?;
+    }
+
+    let mut players: Vec<(Box<dyn Connection + Send>, String)> = vec![];
+    while players.len() < 3 {
+        let mut idle = true;
+
+        if let Some(stream) = try_accept(listener)? {
+            idle = false;
+            players.extend(join(stream, TcpConnection::new));
+        }
+
+        if let Some(stream) = websocket.map(try_accept).transpose()?.flatten() {
+            idle = false;
+            players.extend(join(stream, WebSocketConnection::accept));
+        }
+
+        if idle {
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+    Ok(players)
+}
+
+/* Pending connection of a non-blocking listener, switched back to blocking */
+fn try_accept(listener: &TcpListener) -> io::Result<Option<TcpStream>> {
+    match listener.accept() {
+        Ok((stream, _)) => {
+            stream.set_nonblocking(false)?;
+            Ok(Some(stream))
+        },
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/*
+    A new client has `JOIN_TIMEOUT` to open its connection and send the join
+    message, a silent or failing one only loses its own place in the lobby
+*/
+fn join<C, F>(stream: TcpStream, open: F) -> Option<(Box<dyn Connection + Send>, String)>
+where
+    C: Connection + Send + 'static,
+    F: FnOnce(TcpStream) -> io::Result<C>,
+{
+    stream.set_read_timeout(Some(JOIN_TIMEOUT)).ok()?;
+    let socket = stream.try_clone().ok()?;
+    let mut connection = open(stream).ok()?;
+    let name = read_join(&mut connection)?;
+    socket.set_read_timeout(None).ok()?;
+    Some((Box::new(connection), name))
+}
+
+/* First message of a client must be a join, anything else closes it */
+pub fn read_join(connection: &mut dyn Connection) -> Option<String> {
+    let line = connection.read_line().ok()??;
+    match protocol::decode(&line) {
+        Ok(ClientMessage::Join { name }) => Some(name),
+        _ => {
+            let error = ServerMessage::Error { message: String::from("Send a join message first") };
+            let _ = connection.send_line(&protocol::encode(&error));
+            None
+        }
+    }
+}
+
+/* Play a match with the joined clients, seats in joining order */
+pub fn host(players: Vec<(Box<dyn Connection + Send>, String)>) -> GameEngine {
+    let mut engine = GameEngine::new();
+    let mut seats: Vec<RemoteSeat> = vec![];
+    let mut names: Vec<String> = vec![];
+
+    for (seat, (connection, name)) in players.into_iter().enumerate() {
+        engine.add_player(name.clone(), seat == 0).expect("A table seats three players");
+        let mut remote = RemoteSeat::new(connection);
+        remote.send(&ServerMessage::Welcome { seat });
+        seats.push(remote);
+        names.push(name);
+    }
+    for remote in seats.iter_mut() {
+        for (seat, name) in names.iter().enumerate() {
+            remote.send(&ServerMessage::Join { seat, name: name.clone() });
+        }
+    }
+
+    while let Some(decision) = engine.pending_decision() {
+        if seats.iter().all(|s| !s.is_connected()) {
+            break;
+        }
+        if controller::ask_until_accepted(&mut engine, &mut seats, decision).is_err() {
+            break;
+        }
+    }
+
+    let scores = protocol::scores(&engine);
+    for remote in seats.iter_mut() {
+        remote.send(&ServerMessage::GameOver { scores: scores.clone() });
+    }
+    engine
+}
+
+/* Accept three clients and host their match */
+pub fn serve(listener: &TcpListener, websocket: Option<&TcpListener>) -> io::Result<GameEngine> {
+    let players = accept_players(listener, websocket)?;
+    Ok(host(players))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::net::protocol::Request;
+
+    /* Answers every request with the first allowed cards, leaves after the first deal */
+    fn scripted_client(mut connection: Box<dyn Connection>, name: &str) -> Vec<ServerMessage> {
+        let join = ClientMessage::Join { name: name.to_string() };
+        connection.send_line(&protocol::encode(&join)).unwrap();
+
+        let mut seen = vec![];
+        while let Some(line) = connection.read_line().unwrap() {
+            let message: ServerMessage = protocol::decode(&line).unwrap();
+            seen.push(message.clone());
+            match message {
+                ServerMessage::YourTurn { request, legal } => {
+                    let answer = match request {
+                        Request::Play => ClientMessage::Play { card: legal[0] },
+                        Request::Hide => ClientMessage::Hide { cards: [legal[0], legal[1]] },
+                        Request::Trump => ClientMessage::Trump { suit: Suit::Hearts },
+                    };
+                    connection.send_line(&protocol::encode(&answer)).unwrap();
+                },
+                ServerMessage::DealOver { .. } => break,
+                _ => {}
+            }
+        }
+        seen
+    }
+
+    /* Seats 0 and 1 join over TCP, seat 2 through the WebSocket gateway */
+    fn play_first_deal(websocket_seat: bool) -> Vec<Vec<ServerMessage>> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let gateway = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let ws_address = gateway.local_addr().unwrap();
+        let server = thread::spawn(move || serve(&listener, Some(&gateway)).unwrap());
+
+        let clients: Vec<_> = ["Ilia", "Sandro", "Levani"]
+            .iter()
+            .enumerate()
+            .map(|(seat, name)| {
+                let client = thread::spawn(move || {
+                    let connection: Box<dyn Connection> = if websocket_seat && seat == 2 {
+                        let stream = TcpStream::connect(ws_address).unwrap();
+                        let url = format!("ws://{}/", ws_address);
+                        Box::new(WebSocketConnection::connect(stream, &url).unwrap())
+                    } else {
+                        Box::new(TcpConnection::new(TcpStream::connect(address).unwrap()).unwrap())
+                    };
+                    scripted_client(connection, name)
+                });
+                /* Join one after another so seats follow the list */
+                thread::sleep(Duration::from_millis(100));
+                client
+            })
+            .collect();
+        let seen = clients.into_iter().map(|c| c.join().unwrap()).collect();
+        server.join().unwrap();
+        seen
+    }
+
+    fn check_private_view(seen: &[Vec<ServerMessage>]) {
+        for (seat, messages) in seen.iter().enumerate() {
+            assert_eq!(messages[0], ServerMessage::Welcome { seat });
+            let hand = messages.iter().find_map(|m| match m {
+                ServerMessage::Deal { hand, .. } => Some(hand.len()),
+                _ => None,
+            });
+            assert_eq!(hand, Some(if seat == 0 { 12 } else { 10 }));
+
+            /* Only the declarer learns which cards were hidden */
+            for message in messages {
+                if let ServerMessage::CardsHidden { cards, .. } = message {
+                    assert_eq!(cards.is_some(), seat == 0);
+                }
+            }
+            assert!(messages.iter().any(|m| matches!(m, ServerMessage::DealOver { .. })));
+        }
+    }
+
+    #[test]
+    fn a_silent_client_does_not_hold_up_the_lobby() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let lobby = thread::spawn(move || {
+            let players = accept_players(&listener, None).unwrap();
+            players.into_iter().map(|(_, name)| name).collect::<Vec<_>>()
+        });
+
+        let _silent = TcpStream::connect(address).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        let clients: Vec<TcpConnection> = ["Ilia", "Sandro", "Levani"]
+            .iter()
+            .map(|name| {
+                let mut connection = TcpConnection::new(TcpStream::connect(address).unwrap()).unwrap();
+                let join = ClientMessage::Join { name: name.to_string() };
+                connection.send_line(&protocol::encode(&join)).unwrap();
+                connection
+            })
+            .collect();
+
+        assert_eq!(lobby.join().unwrap(), vec!["Ilia", "Sandro", "Levani"]);
+        drop(clients);
+    }
+
+    #[test]
+    fn three_clients_play_a_deal_on_localhost() {
+        check_private_view(&play_first_deal(false));
+    }
+
+    #[test]
+    fn websocket_client_plays_next_to_tcp_clients() {
+        let seen = play_first_deal(true);
+        check_private_view(&seen);
+
+        /* The browser seat saw the same public story as a TCP seat */
+        let public = |messages: &Vec<ServerMessage>| -> Vec<ServerMessage> {
+            messages
+                .iter()
+                .filter(|m| matches!(m, ServerMessage::Play { .. } | ServerMessage::TrickWon { .. }))
+                .cloned()
+                .collect()
+        };
+        assert_eq!(public(&seen[2]), public(&seen[1]));
+    }
+}
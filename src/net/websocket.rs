@@ -0,0 +1,59 @@
+/*
+    WebSocket transport for browser clients. Every text frame carries one
+    message of the same JSON schema the TCP clients use, without the newline.
+*/
+
+use std::io;
+use std::net::TcpStream;
+
+use tungstenite::{Message, WebSocket};
+
+use crate::net::server::Connection;
+
+pub struct WebSocketConnection {
+    socket: WebSocket<TcpStream>,
+}
+
+impl WebSocketConnection {
+    /* Finish the HTTP upgrade of a freshly accepted stream */
+    pub fn accept(stream: TcpStream) -> io::Result<Self> {
+        let socket = tungstenite::accept(stream)
+            .map_err(|e| io::Error::new(io::ErrorKind::ConnectionAborted, e.to_string()))?;
+        Ok(WebSocketConnection { socket })
+    }
+
+    /* Client side, used by tests and tools talking to the gateway */
+    pub fn connect(stream: TcpStream, url: &str) -> io::Result<Self> {
+        let (socket, _) = tungstenite::client(url, stream)
+            .map_err(|e| io::Error::new(io::ErrorKind::ConnectionRefused, e.to_string()))?;
+        Ok(WebSocketConnection { socket })
+    }
+}
+
+fn to_io(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        other => io::Error::other(other.to_string()),
+    }
+}
+
+impl Connection for WebSocketConnection {
+    fn send_line(&mut self, line: &str) -> io::Result<()> {
+        self.socket
+            .send(Message::Text(line.trim_end().to_string()))
+            .map_err(to_io)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        loop {
+            match self.socket.read() {
+                Ok(Message::Text(text)) => return Ok(Some(text)),
+                Ok(Message::Close(_)) => return Ok(None),
+                /* Pings are answered by tungstenite itself, binary frames are not part of the protocol */
+                Ok(_) => continue,
+                Err(tungstenite::Error::ConnectionClosed) | Err(tungstenite::Error::AlreadyClosed) => return Ok(None),
+                Err(error) => return Err(to_io(error)),
+            }
+        }
+    }
+}
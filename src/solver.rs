@@ -0,0 +1,403 @@
+/*
+    Perfect-information (double-dummy) solver for a single deal.
+
+    With every hand face up, the solver finds for each seat the score it can
+    guarantee when the other two seats play against it (paranoid search).
+    It follows the same follow-suit and trump obligations as the engine,
+    scores tricks with `Board::handle_current_game` and stops the deal as
+    soon as the King Heart, the fourth queen or the fourth jack is taken.
+
+    Hands are kept as bit masks, positions at the start of a trick are cached
+    in a transposition table, and cards of one hand that are next to each
+    other among the remaining cards are searched only once.
+*/
+
+use std::collections::HashMap;
+
+use crate::bots::winning_card;
+use crate::game::board::Board;
+use crate::game::card::{Card, Rank, Suit};
+use crate::game::contract::Contract;
+
+/* Deal position to solve: the hands still held and the cards already on the board */
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub hands: [Vec<Card>; 3],
+    pub leader: usize,
+    pub trick: Vec<Card>, // cards of the unfinished trick, in play order from the leader
+    pub contract: Contract,
+    pub trump: Option<Suit>,
+}
+
+impl Position {
+    /* Position at the start of a deal (after the declarer hid two cards) */
+    pub fn new(hands: [Vec<Card>; 3], leader: usize, contract: Contract, trump: Option<Suit>) -> Self {
+        Position { hands, leader, trick: vec![], contract, trump }
+    }
+
+    /* Seat whose card is next */
+    pub fn to_move(&self) -> usize {
+        (self.leader + self.trick.len()) % 3
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Bound {
+    lower: i32,
+    upper: i32,
+}
+
+#[derive(Clone, Copy)]
+struct State {
+    hands: [u32; 3],
+    leader: usize,
+    trick: [usize; 3],
+    trick_len: usize,
+}
+
+pub struct Solver {
+    contract: Contract,
+    trump: Option<Suit>,
+    board: Board,
+    table: HashMap<([u32; 3], usize), Bound>,
+    nodes: u64,
+}
+
+fn card_idx(card: &Card) -> usize {
+    let suit = Suit::ALL.iter().position(|s| *s == card.suit).unwrap();
+    suit * 8 + card.rank as usize
+}
+
+fn idx_card(idx: usize) -> Card {
+    Card::new(Suit::ALL[idx / 8], Rank::ALL[idx % 8])
+}
+
+fn suit_mask(suit: Suit) -> u32 {
+    let suit = Suit::ALL.iter().position(|s| *s == suit).unwrap();
+    0xff << (suit * 8)
+}
+
+fn to_mask(cards: &[Card]) -> u32 {
+    cards.iter().fold(0, |mask, c| mask | 1 << card_idx(c))
+}
+
+impl Solver {
+    pub fn new(contract: Contract, trump: Option<Suit>) -> Self {
+        Solver { contract, trump, board: Board::new(), table: HashMap::new(), nodes: 0 }
+    }
+
+    /* Nodes visited since the solver was created */
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /* Score every seat can guarantee from this position to the end of the deal */
+    pub fn solve(position: &Position) -> [i32; 3] {
+        let mut solver = Solver::new(position.contract, position.trump);
+        let mut result = [0; 3];
+        for (seat, score) in result.iter_mut().enumerate() {
+            *score = solver.value(position, seat);
+        }
+        result
+    }
+
+    /* Guaranteed score of `seat` from this position */
+    pub fn value(&mut self, position: &Position, seat: usize) -> i32 {
+        self.table.clear();
+        let mut state = Self::state(position);
+        self.search(&mut state, seat, i32::MIN / 2, i32::MAX / 2)
+    }
+
+    /* Guaranteed score of the seat to move after each of its legal cards */
+    pub fn evaluate_moves(&mut self, position: &Position) -> Vec<(Card, i32)> {
+        let seat = position.to_move();
+        let state = Self::state(position);
+        let moves = self.legal(&state, seat);
+        let mut result = vec![];
+        for idx in 0..32 {
+            if moves & 1 << idx == 0 {
+                continue;
+            }
+            let mut next = position.clone();
+            let card = idx_card(idx);
+            next.hands[seat].retain(|c| *c != card);
+            next.trick.push(card);
+
+            let mut gained = 0;
+            if next.trick.len() == 3 {
+                let child = Self::state(&next);
+                let (winner, score, over) = self.finish_trick(&child, &next.trick);
+                if winner == seat {
+                    gained = score;
+                }
+                next.leader = winner;
+                next.trick.clear();
+                if over {
+                    result.push((card, gained));
+                    continue;
+                }
+            }
+            result.push((card, gained + self.value(&next, seat)));
+        }
+        result
+    }
+
+    fn state(position: &Position) -> State {
+        let mut trick = [0; 3];
+        for (i, c) in position.trick.iter().enumerate() {
+            trick[i] = card_idx(c);
+        }
+        State {
+            hands: [
+                to_mask(&position.hands[0]),
+                to_mask(&position.hands[1]),
+                to_mask(&position.hands[2]),
+            ],
+            leader: position.leader,
+            trick,
+            trick_len: position.trick.len(),
+        }
+    }
+
+    /* Cards the seat may play, same rules as `GameEngine::legal_moves` */
+    fn legal(&self, state: &State, seat: usize) -> u32 {
+        let hand = state.hands[seat];
+        if state.trick_len == 0 {
+            let king_heart = 1 << card_idx(&Card::new(Suit::Hearts, Rank::King));
+            let hearts = suit_mask(Suit::Hearts);
+            if self.contract == Contract::King && hand & king_heart != 0 && hand & !hearts != 0 {
+                return hand & !king_heart;
+            }
+            return hand;
+        }
+
+        let lead = suit_mask(idx_card(state.trick[0]).suit);
+        if hand & lead != 0 {
+            return hand & lead;
+        }
+        if let Some(trump) = self.trump {
+            if hand & suit_mask(trump) != 0 {
+                return hand & suit_mask(trump);
+            }
+        }
+        hand
+    }
+
+    /* Drop cards equivalent to a higher card of the same hand */
+    fn reduce(&self, state: &State, moves: u32, seat: usize) -> u32 {
+        let mut live = state.hands[0] | state.hands[1] | state.hands[2];
+        for c in &state.trick[..state.trick_len] {
+            live |= 1 << c;
+        }
+        let own = state.hands[seat];
+
+        let mut result = 0;
+        for idx in 0..32 {
+            if moves & 1 << idx == 0 {
+                continue;
+            }
+            /* Next live card above in the same suit */
+            let mut above = None;
+            for higher in idx + 1..(idx / 8 + 1) * 8 {
+                if live & 1 << higher != 0 {
+                    above = Some(higher);
+                    break;
+                }
+            }
+            let equivalent = match above {
+                Some(higher) => {
+                    own & 1 << higher != 0 &&
+                    moves & 1 << higher != 0 &&
+                    self.contract.is_penalty_card(&idx_card(idx)) == self.contract.is_penalty_card(&idx_card(higher))
+                },
+                None => false,
+            };
+            if !equivalent {
+                result |= 1 << idx;
+            }
+        }
+        result
+    }
+
+    /* Winner, score and whether the deal ends, for a complete trick */
+    fn finish_trick(&mut self, state: &State, cards: &[Card]) -> (usize, i32, bool) {
+        let best = winning_card(cards, self.trump).unwrap();
+        let offset = cards.iter().position(|c| *c == best).unwrap();
+        let winner = (state.leader + offset) % 3;
+
+        let remaining = state.hands[winner].count_ones();
+        let is_last_two = remaining < 2;
+        let score = self.board.handle_current_game(self.contract, cards, is_last_two);
+        let score = if score == -1 { 0 } else { score };
+
+        let left = state.hands[0] | state.hands[1] | state.hands[2];
+        let rank_left = |rank: Rank| Suit::ALL.iter().any(|s| left & 1 << card_idx(&Card::new(*s, rank)) != 0);
+        let over = remaining == 0 || match self.contract {
+            Contract::King => score != 0,
+            Contract::Queens => score != 0 && !rank_left(Rank::Queen),
+            Contract::Jacks => score != 0 && !rank_left(Rank::Jack),
+            _ => false,
+        };
+        (winner, score, over)
+    }
+
+    fn search(&mut self, state: &mut State, me: usize, mut alpha: i32, mut beta: i32) -> i32 {
+        self.nodes += 1;
+        let at_trick_start = state.trick_len == 0;
+        let key = (state.hands, state.leader);
+        let (alpha_in, beta_in) = (alpha, beta);
+
+        if at_trick_start {
+            if let Some(bound) = self.table.get(&key) {
+                if bound.lower == bound.upper || bound.lower >= beta {
+                    return bound.lower;
+                }
+                if bound.upper <= alpha {
+                    return bound.upper;
+                }
+            }
+        }
+
+        let seat = (state.leader + state.trick_len) % 3;
+        let maximizing = seat == me;
+        let moves = self.reduce(state, self.legal(state, seat), seat);
+        if moves == 0 {
+            return 0;
+        }
+        let mut best = if maximizing { i32::MIN } else { i32::MAX };
+
+        /* High cards first, they settle tricks early */
+        for idx in (0..32).rev() {
+            if moves & 1 << idx == 0 {
+                continue;
+            }
+            let saved = *state;
+            state.hands[seat] &= !(1 << idx);
+            state.trick[state.trick_len] = idx;
+            state.trick_len += 1;
+
+            let value = if state.trick_len == 3 {
+                let cards: Vec<Card> = state.trick.iter().map(|c| idx_card(*c)).collect();
+                let (winner, score, over) = self.finish_trick(state, &cards);
+                let gained = if winner == me { score } else { 0 };
+                if over {
+                    gained
+                } else {
+                    state.leader = winner;
+                    state.trick_len = 0;
+                    gained + self.search(state, me, alpha - gained, beta - gained)
+                }
+            } else {
+                self.search(state, me, alpha, beta)
+            };
+            *state = saved;
+
+            if maximizing {
+                best = best.max(value);
+                alpha = alpha.max(value);
+            } else {
+                best = best.min(value);
+                beta = beta.min(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if at_trick_start {
+            let entry = self.table.entry(key).or_insert(Bound { lower: i32::MIN, upper: i32::MAX });
+            if best <= alpha_in {
+                entry.upper = best;
+            } else if best >= beta_in {
+                entry.lower = best;
+            } else {
+                entry.lower = best;
+                entry.upper = best;
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::RandomBot;
+    use crate::game::controller::PlayerController;
+    use crate::game::engine::{Action, Decision, GameEngine};
+
+    fn cards(list: &[&str]) -> Vec<Card> {
+        list.iter().map(|c| c.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn king_heart_is_forced_on_the_ace_holder() {
+        let position = Position::new(
+            [cards(&["KH", "7S"]), cards(&["AH", "8S"]), cards(&["7H", "9S"])],
+            0,
+            Contract::King,
+            None,
+        );
+        assert_eq!(Solver::solve(&position), [0, -40, 0]);
+    }
+
+    #[test]
+    fn plus_game_counts_every_trick_taken() {
+        let position = Position::new(
+            [cards(&["7C", "8C", "AS"]), cards(&["KS", "QS", "JS"]), cards(&["AD", "KD", "QD"])],
+            1,
+            Contract::Plus,
+            Some(Suit::Clubs),
+        );
+        /* Seat 0 must follow the first spade lead with the ace, then leads its two trumps and takes every trick */
+        assert_eq!(Solver::solve(&position)[0], 12);
+    }
+
+    #[test]
+    fn evaluate_moves_agrees_with_value() {
+        let position = Position::new(
+            [cards(&["KH", "7S", "QD"]), cards(&["AH", "8S", "9D"]), cards(&["7H", "9S", "10D"])],
+            0,
+            Contract::King,
+            None,
+        );
+        let mut solver = Solver::new(Contract::King, None);
+        let best = solver.evaluate_moves(&position).iter().map(|(_, v)| *v).max().unwrap();
+        assert_eq!(best, solver.value(&position, 0));
+    }
+
+    #[test]
+    fn legal_cards_match_the_engine() {
+        let mut engine = GameEngine::new();
+        for (seat, name) in ["A", "B", "C"].iter().enumerate() {
+            engine.add_player(name.to_string(), seat == 0).unwrap();
+        }
+        let mut bot = RandomBot::with_seed(7);
+        for _ in 0..600 {
+            let action = match engine.pending_decision().unwrap() {
+                Decision::StartDeal => Action::StartDeal,
+                Decision::HideCards { seat } => Action::HideCards { seat, cards: bot.choose_cards_to_hide(&engine.observation(seat)).unwrap() },
+                Decision::ChooseTrump { seat } => Action::ChooseTrump { seat, trump: bot.choose_trump(&engine.observation(seat)).unwrap() },
+                Decision::PlayCard { seat } => {
+                    let trick = engine.board.trick.cards();
+                    let position = Position {
+                        hands: [
+                            engine.players[0].hand.clone(),
+                            engine.players[1].hand.clone(),
+                            engine.players[2].hand.clone(),
+                        ],
+                        leader: (seat + 3 - trick.len()) % 3,
+                        trick,
+                        contract: engine.contract().unwrap(),
+                        trump: engine.trump(),
+                    };
+                    let solver = Solver::new(position.contract, position.trump);
+                    let legal = solver.legal(&Solver::state(&position), seat);
+                    assert_eq!(legal, to_mask(&engine.legal_moves(seat)));
+                    Action::PlayCard { seat, card: bot.choose_card(&engine.observation(seat)).unwrap() }
+                },
+            };
+            engine.apply(action).unwrap();
+        }
+    }
+}
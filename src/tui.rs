@@ -0,0 +1,453 @@
+/*
+    Full-screen terminal front end. A `TuiController` takes a human seat: it
+    draws the table with ratatui and lets the player pick cards with the arrow
+    keys, where only the cards the engine allows can be selected.
+
+    All TUI seats share one `Screen`, which enters the alternate screen on the
+    first draw and leaves it again on `close` or when dropped.
+*/
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind};
+use ratatui::layout::{Alignment, Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::game::card::{Card, Suit};
+use crate::game::controller::PlayerController;
+use crate::game::engine::{Event, GameError};
+use crate::game::observation::Observation;
+use crate::game::save;
+
+const TRICK_PAUSE: Duration = Duration::from_millis(700);
+const MESSAGES: usize = 4;
+
+/* The terminal, opened lazily so plain-text prompts can run in between */
+#[derive(Default)]
+pub struct Screen {
+    terminal: Option<DefaultTerminal>,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Screen { terminal: None }
+    }
+
+    /* Shared handle for several seats */
+    pub fn shared() -> Rc<RefCell<Screen>> {
+        Rc::new(RefCell::new(Screen::new()))
+    }
+
+    pub fn draw(&mut self, render: impl FnOnce(&mut Frame)) {
+        let terminal = self.terminal.get_or_insert_with(ratatui::init);
+        /* A failed draw only loses one frame */
+        let _ = terminal.draw(render);
+    }
+
+    /* Back to the normal screen */
+    pub fn close(&mut self) {
+        if self.terminal.take().is_some() {
+            ratatui::restore();
+        }
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+pub fn suit_symbol(suit: Suit) -> &'static str {
+    match suit {
+        Suit::Hearts => "♥",
+        Suit::Diamonds => "♦",
+        Suit::Spades => "♠",
+        Suit::Clubs => "♣",
+    }
+}
+
+pub fn suit_color(suit: Suit) -> Color {
+    match suit {
+        Suit::Hearts | Suit::Diamonds => Color::Red,
+        Suit::Spades | Suit::Clubs => Color::White,
+    }
+}
+
+pub fn card_span(card: Card, style: Style) -> Span<'static> {
+    let text = format!("{}{}", card.rank.as_str(), suit_symbol(card.suit));
+    Span::styled(text, style.fg(suit_color(card.suit)))
+}
+
+/* Hand grouped by suit, low to high inside a suit */
+pub fn sorted_hand(hand: &[Card]) -> Vec<Card> {
+    let mut hand = hand.to_vec();
+    hand.sort_by_key(|c| (c.suit, c.rank));
+    hand
+}
+
+/* What one frame shows, from the point of view of `seat` */
+pub struct View<'a> {
+    pub observation: &'a Observation,
+    pub trick: &'a [(usize, Card)],
+    pub selectable: &'a [Card],
+    pub cursor: Option<Card>,
+    pub marked: &'a [Card],
+    pub trump_cursor: Option<Suit>,
+    pub prompt: &'a str,
+    pub messages: &'a [String],
+}
+
+pub fn draw(frame: &mut Frame, view: &View) {
+    let seen = view.observation;
+    let names = &seen.names;
+    let [header, table, hand, footer] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Min(5),
+        Constraint::Length(4),
+        Constraint::Length(MESSAGES as u16 + 2),
+    ])
+    .areas(frame.area());
+
+    /* Contract, declarer, trump and scores */
+    let contract = seen.contract.map_or("Match over", |c| c.name());
+    let trump = match seen.trump {
+        Some(suit) => Span::styled(suit_symbol(suit), Style::default().fg(suit_color(suit))),
+        None => Span::raw("-"),
+    };
+    let declarer = names.get(seen.declarer).map_or("", String::as_str);
+    let scores: Vec<Span> = names
+        .iter()
+        .zip(&seen.scores)
+        .map(|(name, score)| Span::raw(format!("{}: {}   ", name, score)))
+        .collect();
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(contract, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!("   declarer {}   trump ", declarer)),
+            trump,
+        ]),
+        Line::from(scores),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" King ")), header);
+
+    /* Current trick, one line per card in play order */
+    let trick: Vec<Line> = view.trick
+        .iter()
+        .map(|(seat, card)| {
+            Line::from(vec![
+                Span::raw(format!("{:>12}  ", names[*seat])),
+                card_span(*card, Style::default().add_modifier(Modifier::BOLD)),
+            ])
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(trick).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL).title(" Trick ")),
+        table,
+    );
+
+    /* Own hand, cards outside the selectable set are dimmed */
+    let mut cards: Vec<Span> = vec![];
+    for card in sorted_hand(&seen.hand) {
+        let mut style = Style::default();
+        if !view.selectable.contains(&card) {
+            style = style.add_modifier(Modifier::DIM);
+        }
+        if view.marked.contains(&card) {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        if view.cursor == Some(card) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        cards.push(card_span(card, style));
+        cards.push(Span::raw(" "));
+    }
+    let mut lines = vec![Line::from(cards)];
+    if let Some(selected) = view.trump_cursor {
+        let suits: Vec<Span> = Suit::ALL
+            .iter()
+            .flat_map(|suit| {
+                let mut style = Style::default().fg(suit_color(*suit));
+                if *suit == selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                [Span::styled(suit_symbol(*suit), style), Span::raw("  ")]
+            })
+            .collect();
+        lines.push(Line::from(suits));
+    }
+    let title = format!(" {} ", names[seen.seat]);
+    frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), hand);
+
+    let mut lines: Vec<Line> = view.messages.iter().map(|m| Line::from(m.as_str())).collect();
+    lines.push(Line::from(Span::styled(view.prompt, Style::default().fg(Color::Yellow))));
+    frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL)), footer);
+}
+
+/* Human seat played on the shared screen */
+pub struct TuiController {
+    screen: Rc<RefCell<Screen>>,
+    trick: Vec<(usize, Card)>,
+    messages: Vec<String>,
+    pause: Duration,
+}
+
+impl TuiController {
+    pub fn new(screen: Rc<RefCell<Screen>>) -> Self {
+        TuiController { screen, trick: vec![], messages: vec![], pause: TRICK_PAUSE }
+    }
+
+    /* How long a finished trick stays on the table */
+    pub fn with_pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    fn message(&mut self, text: String) {
+        self.messages.push(text);
+        if self.messages.len() > MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    fn redraw(&self, seen: &Observation, selection: &Selection, prompt: &str) {
+        let view = View {
+            observation: seen,
+            trick: &self.trick,
+            selectable: &selection.selectable,
+            cursor: selection.cursor(),
+            marked: &selection.marked,
+            trump_cursor: selection.trump,
+            prompt,
+            messages: &self.messages,
+        };
+        self.screen.borrow_mut().draw(|frame| draw(frame, &view));
+    }
+
+    /* Next key press, 's' saves the match and 'q' quits it */
+    fn key(&mut self) -> Result<KeyCode, GameError> {
+        loop {
+            let code = match event::read() {
+                Ok(TermEvent::Key(key)) if key.kind == KeyEventKind::Press => key.code,
+                Ok(_) => continue,
+                Err(_) => KeyCode::Char('q'),
+            };
+            match code {
+                KeyCode::Char('q') | KeyCode::Esc => return Err(GameError::Quit),
+                KeyCode::Char('s') => return Err(GameError::Save(String::from(save::AUTOSAVE_FILE))),
+                code => return Ok(code),
+            }
+        }
+    }
+}
+
+/* Cursor over the cards (or suits) the player may pick */
+struct Selection {
+    selectable: Vec<Card>, // in the order the hand is drawn
+    index: usize,
+    marked: Vec<Card>,
+    trump: Option<Suit>,
+}
+
+impl Selection {
+    fn cards(hand: &[Card], allowed: impl Fn(&Card) -> bool) -> Self {
+        let selectable = sorted_hand(hand).into_iter().filter(|c| allowed(c)).collect();
+        Selection { selectable, index: 0, marked: vec![], trump: None }
+    }
+
+    fn cursor(&self) -> Option<Card> {
+        self.selectable.get(self.index).copied()
+    }
+
+    fn step(&mut self, code: KeyCode) {
+        let len = if self.trump.is_some() { Suit::ALL.len() } else { self.selectable.len() };
+        if len == 0 {
+            return;
+        }
+        match code {
+            KeyCode::Left | KeyCode::Char('h') => self.index = (self.index + len - 1) % len,
+            KeyCode::Right | KeyCode::Char('l') => self.index = (self.index + 1) % len,
+            _ => return,
+        }
+        if self.trump.is_some() {
+            self.trump = Some(Suit::ALL[self.index]);
+        }
+    }
+}
+
+impl PlayerController for TuiController {
+    fn choose_card(&mut self, seen: &Observation) -> Result<Card, GameError> {
+        let mut selection = Selection::cards(&seen.hand, |c| seen.legal_moves.contains(c));
+        loop {
+            self.redraw(seen, &selection, "←/→ choose a card, Enter plays it, s saves, q quits");
+            match self.key()? {
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    if let Some(card) = selection.cursor() {
+                        return Ok(card);
+                    }
+                },
+                code => selection.step(code),
+            }
+        }
+    }
+
+    fn choose_trump(&mut self, seen: &Observation) -> Result<Suit, GameError> {
+        let mut selection = Selection::cards(&[], |_| false);
+        selection.trump = Some(Suit::ALL[0]);
+        loop {
+            self.redraw(seen, &selection, "←/→ choose the trump suit, Enter announces it");
+            match self.key()? {
+                KeyCode::Enter | KeyCode::Char(' ') => return Ok(selection.trump.unwrap()),
+                code => selection.step(code),
+            }
+        }
+    }
+
+    fn choose_cards_to_hide(&mut self, seen: &Observation) -> Result<[Card; 2], GameError> {
+        let allowed = seen.discardable();
+        let mut selection = Selection::cards(&seen.hand, |c| allowed.contains(c));
+        loop {
+            self.redraw(seen, &selection, "←/→ move, Space marks a card to hide, Enter hides the two marked");
+            match self.key()? {
+                KeyCode::Char(' ') => {
+                    if let Some(card) = selection.cursor() {
+                        if let Some(i) = selection.marked.iter().position(|c| *c == card) {
+                            selection.marked.remove(i);
+                        } else if selection.marked.len() < 2 {
+                            selection.marked.push(card);
+                        }
+                    }
+                },
+                KeyCode::Enter if selection.marked.len() == 2 => {
+                    return Ok([selection.marked[0], selection.marked[1]]);
+                },
+                code => selection.step(code),
+            }
+        }
+    }
+
+    fn rejected(&mut self, _seen: &Observation, error: GameError) {
+        self.message(error.to_string());
+    }
+
+    fn inform(&mut self, text: &str) {
+        self.message(text.to_string());
+    }
+
+    fn notify(&mut self, seen: &Observation, event: &Event) {
+        let idle = Selection::cards(&[], |_| false);
+        match event {
+            Event::DealStarted { contract, declarer } => {
+                self.trick.clear();
+                self.message(format!("{} declares {}", seen.names[*declarer], contract.name()));
+            },
+            Event::TrumpChosen { trump, .. } => self.message(format!("Trump is {}", suit_symbol(*trump))),
+            Event::CardPlayed { seat, card } => {
+                /* The table still shows the last trick until the next card */
+                if self.trick.len() == 3 {
+                    self.trick.clear();
+                }
+                self.trick.push((*seat, *card));
+            },
+            Event::TrickWon { seat, card, .. } => {
+                let text = format!("{}{} takes it, the trick goes to {}", card.rank.as_str(), suit_symbol(card.suit), seen.names[*seat]);
+                self.message(text);
+                self.redraw(seen, &idle, "");
+                thread::sleep(self.pause);
+            },
+            Event::ScoreChanged { seat, delta, .. } => {
+                self.message(format!("{} gets {}", seen.names[*seat], delta));
+            },
+            Event::MatchOver { standings } => {
+                for standing in standings {
+                    self.message(format!("{}. {}: {}", standing.place, seen.names[standing.seat], standing.score));
+                }
+                self.redraw(seen, &idle, "The match is over, press any key");
+                let _ = self.key();
+                return;
+            },
+            _ => {}
+        }
+        self.redraw(seen, &idle, "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+    use crate::game::engine::GameEngine;
+
+    fn card(notation: &str) -> Card {
+        notation.parse().unwrap()
+    }
+
+    #[test]
+    fn hands_are_sorted_by_suit_then_rank() {
+        let hand = [card("AS"), card("7H"), card("10S"), card("KH")];
+        assert_eq!(sorted_hand(&hand), vec![card("7H"), card("KH"), card("10S"), card("AS")]);
+    }
+
+    #[test]
+    fn table_shows_symbols_trick_and_scores() {
+        let mut engine = GameEngine::new();
+        for name in ["Ilia", "Sandro", "Levani"] {
+            engine.add_player(String::from(name), false).unwrap();
+        }
+        engine.players[0].hand = vec![card("10H"), card("7S")];
+        engine.players[1].score = -40;
+        let trick = [(1, card("QD"))];
+        let selectable = [card("7S")];
+
+        let mut terminal = Terminal::new(TestBackend::new(70, 20)).unwrap();
+        terminal
+            .draw(|frame| {
+                draw(frame, &View {
+                    observation: &engine.observation(0),
+                    trick: &trick,
+                    selectable: &selectable,
+                    cursor: Some(card("7S")),
+                    marked: &[],
+                    trump_cursor: None,
+                    prompt: "your turn",
+                    messages: &[String::from("hello")],
+                })
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("King"));
+        assert!(text.contains("Sandro: -40"));
+        assert!(text.contains("Q♦"));
+        assert!(text.contains("10♥"));
+        assert!(text.contains("your turn"));
+
+        /* Only the legal card is highlighted, the other one is dimmed */
+        let cell = |symbol: &str| buffer.content().iter().find(|c| c.symbol() == symbol).unwrap().clone();
+        assert!(cell("7").modifier.contains(Modifier::REVERSED));
+        assert!(cell("♥").modifier.contains(Modifier::DIM));
+        assert_eq!(cell("♥").fg, Color::Red);
+    }
+
+    #[test]
+    fn cursor_only_walks_the_selectable_cards() {
+        let hand = [card("AS"), card("7H"), card("10S"), card("KH")];
+        let mut selection = Selection::cards(&hand, |c| c.suit == Suit::Spades);
+        assert_eq!(selection.cursor(), Some(card("10S")));
+        selection.step(KeyCode::Right);
+        assert_eq!(selection.cursor(), Some(card("AS")));
+        selection.step(KeyCode::Right);
+        assert_eq!(selection.cursor(), Some(card("10S")));
+        selection.step(KeyCode::Left);
+        assert_eq!(selection.cursor(), Some(card("AS")));
+    }
+}